@@ -13,6 +13,10 @@ fn main() {
         let mut ks = KyrylScript::new();
         let ks_result = ks.run_from_file(path);
 
+        for warning in ks.get_warnings() {
+            eprintln!("{}", warning);
+        }
+
         if let Err(e) = ks_result {
             println!("{}", e);
         }
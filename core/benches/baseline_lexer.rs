@@ -0,0 +1,179 @@
+// The line based lexer that the single-pass scanner replaced, kept only as a benchmark baseline
+use core::lexer::token::Token;
+use core::lexer::token::COMMENT;
+use core::lexer::token::{get_token, is_symbol};
+use core::lexer::token_pos::TokenPos;
+
+
+enum LexerState {
+    None,
+    String,
+    Number,
+    Identifier,
+    Symbol
+}
+
+pub struct BaselineLexer {
+    tokens: Vec<Token>,
+    token_pos: Vec<TokenPos>,
+    source_lines: Vec<String>,
+    current_line_pos: i32
+}
+
+impl BaselineLexer {
+    pub fn new(source: String) -> BaselineLexer {
+        let source_lines: Vec<String> = source.lines().map(|s| s.to_string()).collect();
+
+        BaselineLexer {
+            tokens: Vec::new(),
+            token_pos: Vec::new(),
+            source_lines,
+            current_line_pos: 0
+        }
+    }
+
+    pub fn get_tokens(&self) -> &Vec<Token> {
+        &self.tokens
+    }
+
+    fn add_token(&mut self, token: Token) {
+        self.tokens.push(token);
+        self.token_pos.push(TokenPos::from(None, self.current_line_pos, 0));
+    }
+
+    fn add_token_text(&mut self, buffer: &str) {
+        if let Some(keyword) = get_token(buffer) {
+            self.add_token(keyword);
+        } else {
+            self.add_token(Token::Identifier(buffer.to_string()));
+        }
+    }
+
+    fn lex_line(&mut self, mut line: String) -> Result<(), String> {
+        line.push(' ');
+        let mut cur: usize = 0;
+        let mut state = LexerState::None;
+
+        let mut buffer = String::new();
+
+        while cur < line.len() {
+            let current_char = line.chars().nth(cur).unwrap();
+
+            match state {
+                LexerState::None => {
+                    if current_char.is_whitespace() {
+                        if !buffer.is_empty() {
+                            self.add_token_text(&buffer);
+                            buffer.clear();
+                        }
+                    } else if current_char.is_alphabetic() {
+                        state = LexerState::Identifier;
+                        buffer.push(current_char);
+                    } else if current_char.is_numeric() {
+                        state = LexerState::Number;
+                        buffer.push(current_char);
+                    } else if current_char == '"' {
+                        state = LexerState::String;
+                    } else if is_symbol(current_char) {
+                        state = LexerState::Symbol;
+                        buffer.push(current_char);
+                    }
+                }
+
+                LexerState::String => {
+                    if current_char == '"' {
+                        self.add_token(Token::StringLiteral(buffer.clone()));
+                        buffer.clear();
+                        state = LexerState::None;
+                    } else {
+                        buffer.push(current_char);
+                    }
+                }
+
+                LexerState::Number => {
+                    if current_char.is_numeric() || current_char == '.' {
+                        buffer.push(current_char);
+                    } else if current_char == 'f' {
+                        let number = buffer.parse::<f64>().map_err(|_| "Invalid float literal".to_string())?;
+                        self.add_token(Token::FloatLiteral(number));
+                        buffer.clear();
+                        state = LexerState::None;
+                    } else {
+                        let number = buffer.parse::<i64>().map_err(|_| "Invalid integer literal".to_string())?;
+                        self.add_token(Token::IntegerLiteral(number));
+                        buffer.clear();
+                        state = LexerState::None;
+
+                        continue;
+                    }
+                }
+
+                LexerState::Identifier => {
+                    if current_char.is_alphabetic() || current_char.is_numeric() || current_char == '_' {
+                        buffer.push(current_char);
+                    } else {
+                        self.add_token_text(buffer.as_str());
+                        buffer.clear();
+                        state = LexerState::None;
+
+                        continue;
+                    }
+                }
+
+                LexerState::Symbol => {
+                    if is_symbol(current_char) && cur != line.len() {
+                        buffer.push(current_char);
+                    } else {
+                        if buffer.contains(COMMENT) {
+                            break;
+                        }
+
+                        self.get_symbols(&buffer);
+                        buffer.clear();
+                        state = LexerState::None;
+
+                        continue;
+                    }
+                }
+            }
+
+            cur += 1;
+        }
+
+        Ok(())
+    }
+
+    fn get_symbols(&mut self, buffer: &str) {
+        let chars: Vec<char> = buffer.chars().collect();
+        let mut i = 0;
+
+        while i < chars.len() {
+            let mut matched = false;
+
+            for j in (i + 1..=chars.len()).rev() {
+                let slice: String = chars[i..j].iter().collect();
+
+                if let Some(token) = get_token(&slice) {
+                    self.add_token(token);
+                    i = j;
+                    matched = true;
+                    break;
+                }
+            }
+
+            if !matched {
+                i += 1;
+            }
+        }
+    }
+
+    pub fn lexer(&mut self) -> Result<(), String> {
+        for line in self.source_lines.clone() {
+            self.lex_line(line)?;
+
+            self.current_line_pos += 1;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,64 @@
+mod baseline_lexer;
+
+use std::time::{Duration, Instant};
+
+use core::lexer::lexer::Lexer;
+
+use baseline_lexer::BaselineLexer;
+
+// Only syntax that the old lexer understood too, so both produce the same tokens
+const SNIPPET: &str = r#"
+// Sums every value up to the limit, with a fairly long line so per-line cost is visible
+function sum(limit: int): int {
+    let total: int = 0;
+    for i in range(limit) {
+        total += i * 255 + 1000 - (i / 2) * (limit - i) + total / 3 + 42 * i - limit / 7 + 123456;
+    }
+    return total;
+}
+
+let message: string = "the sum of the first ten numbers is computed below, followed by a ratio";
+let ratio: float = 1.5f / 2.0f;
+"#;
+
+fn generate_source(copies: usize) -> String {
+    SNIPPET.repeat(copies)
+}
+
+fn report(name: &str, lines: usize, tokens: usize, elapsed: Duration) {
+    println!(
+        "  {:<9} {:>9} tokens: {:>10.2?} ({:.1} ns/line)",
+        name,
+        tokens,
+        elapsed,
+        elapsed.as_nanos() as f64 / lines as f64
+    );
+}
+
+// Runs the old line based lexer and the single-pass scanner on sources of growing size
+fn main() {
+    for copies in [1_000, 10_000, 100_000] {
+        let source = generate_source(copies);
+        let lines = source.lines().count();
+
+        println!("{:>8} lines", lines);
+
+        let start = Instant::now();
+        let mut baseline = BaselineLexer::new(source.clone());
+        baseline.lexer().unwrap();
+        let baseline_elapsed = start.elapsed();
+
+        report("baseline", lines, baseline.get_tokens().len(), baseline_elapsed);
+
+        let start = Instant::now();
+        let mut lexer = Lexer::new(source);
+        lexer.lexer().unwrap();
+        let elapsed = start.elapsed();
+
+        report("scanner", lines, lexer.get_tokens().len(), elapsed);
+
+        assert_eq!(baseline.get_tokens(), lexer.get_tokens(), "Both lexers must produce the same tokens!");
+
+        println!("  speedup   {:.1}x", baseline_elapsed.as_secs_f64() / elapsed.as_secs_f64());
+    }
+}
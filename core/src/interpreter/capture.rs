@@ -0,0 +1,59 @@
+use std::cell::RefCell;
+use std::io;
+use std::rc::{Rc, Weak};
+
+use super::enviroment::Environment;
+
+
+// The scope a function was defined in. A function stored in that same scope only holds it weakly,
+// otherwise the scope and the function keep each other alive after the scope is left
+#[derive(Debug, Clone)]
+pub enum Capture {
+    Strong(Rc<RefCell<Environment>>),
+    Weak(Weak<RefCell<Environment>>)
+}
+
+impl Capture {
+    pub fn get(&self) -> io::Result<Rc<RefCell<Environment>>> {
+        match self {
+            Capture::Strong(scope) => Ok(scope.clone()),
+            Capture::Weak(scope) => scope.upgrade()
+                .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "The scope of the function no longer exists!"))
+        }
+    }
+
+    // Whether the captured scope is `scope`, the environment a value is about to be stored in
+    pub fn is(&self, scope: &Environment) -> bool {
+        match self {
+            Capture::Strong(captured) => std::ptr::eq(captured.as_ptr(), scope),
+            Capture::Weak(captured) => captured.upgrade().is_some_and(|captured| std::ptr::eq(captured.as_ptr(), scope))
+        }
+    }
+
+    pub fn downgrade(&self) -> Capture {
+        match self {
+            Capture::Strong(scope) => Capture::Weak(Rc::downgrade(scope)),
+            Capture::Weak(_) => self.clone()
+        }
+    }
+
+    // A function read out of its scope may outlive it, e.g. when it is returned, so it owns the scope again
+    pub fn upgrade(&self) -> Capture {
+        match self {
+            Capture::Weak(scope) => match scope.upgrade() {
+                Some(scope) => Capture::Strong(scope),
+                None => self.clone()
+            },
+            Capture::Strong(_) => self.clone()
+        }
+    }
+}
+
+impl PartialEq for Capture {
+    fn eq(&self, other: &Self) -> bool {
+        match (self.get(), other.get()) {
+            (Ok(scope), Ok(other)) => Rc::ptr_eq(&scope, &other),
+            _ => false
+        }
+    }
+}
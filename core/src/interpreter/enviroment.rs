@@ -4,41 +4,65 @@ use std::rc::Rc;
 use std::cell::RefCell;
 use std::sync::atomic::{AtomicU64, Ordering};
 
-use super::value::Value;
+use crate::parser::data_type::DataType;
+
+use super::capture::Capture;
+use super::value::{Value, ValueType};
 use super::variable_slot::VariableSlot;
 
 
 static GLOBAL_REFERENCE_COUNT: AtomicU64 = AtomicU64::new(0);
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Clone)]
 pub struct Environment {
     parent: Option<Rc<RefCell<Environment>>>,
+    // Scope a function was defined in, names of a call resolve through it instead of through the caller
+    closure: Option<Rc<RefCell<Environment>>>,
     values: HashMap<String, u64>,
     references: HashMap<u64, VariableSlot>,
+    // Slots of constants with the name of the constant, any alias of them is read-only too
+    constants: HashMap<u64, String>,
+    // Interfaces implemented in this scope, they resolve like the names of their methods
+    implementations: Vec<(String, DataType)>
+}
+
+// Functions hold the scope they were defined in, which usually holds the function again,
+// so comparing or printing an environment must not walk into its values
+impl PartialEq for Environment {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::eq(self, other)
+    }
+}
+
+impl std::fmt::Debug for Environment {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut names: Vec<&String> = self.values.keys().collect();
+        names.sort();
+
+        f.debug_struct("Environment").field("values", &names).finish()
+    }
 }
 
 impl Environment {
     pub fn new() -> Environment {
         Environment {
             parent: None,
+            closure: None,
             values: HashMap::new(),
             references: HashMap::new(),
+            constants: HashMap::new(),
+            implementations: Vec::new()
         }
     }
 
-    pub fn partially_clone(&self) -> Environment {
-        Environment { 
-            parent: None, 
-            values: self.values.clone(), 
-            references: self.references.clone() 
-        }
-    } 
-
     pub fn with_parent(parent: Rc<RefCell<Environment>>) -> Environment {
         Environment {
             parent: Some(parent),
+            closure: None,
             values: HashMap::new(),
             references: HashMap::new(),
+            constants: HashMap::new(),
+            implementations: Vec::new()
         }
     }
 
@@ -57,27 +81,151 @@ impl Environment {
         &self.references
     }
 
-    pub fn append_environment(&mut self, env: Rc<RefCell<Environment>>) {
-        let env = env.borrow();
+    pub fn set_closure(&mut self, closure: Rc<RefCell<Environment>>) {
+        self.closure = Some(closure);
+    }
+
+    // Scopes this one links to strongly, through its parent, its closure and the functions stored in it
+    fn links(&self) -> Vec<Rc<RefCell<Environment>>> {
+        let mut links: Vec<Rc<RefCell<Environment>>> = self.parent.iter().chain(self.closure.iter()).cloned().collect();
+
+        for slot in self.references.values() {
+            if let VariableSlot::Variable(value) = slot
+                && let ValueType::Function { capture: Capture::Strong(scope), .. } = value.get_type() {
+                links.push(scope.clone());
+            }
+        }
+
+        links
+    }
 
-        for (name, reference) in env.get_values() {
-            self.values.insert(name.clone(), *reference);
+    // Called with the last handle of the interpreter when a scope is left. A function returned from a call
+    // and stored in this scope links back to it through the parent of the call scope, so the scope may only
+    // be kept alive by itself. Then nothing can reach its values anymore and they are dropped to free it
+    pub fn release(scope: Rc<RefCell<Environment>>) {
+        if Rc::strong_count(&scope) == 1 {
+            return;
         }
 
-        for (reference, slot) in env.get_references() {
-            self.references.insert(*reference, slot.clone());
+        // Scopes still on the stack are alive anyway, the search stops there
+        let mut outer: Vec<Rc<RefCell<Environment>>> = Vec::new();
+        let mut pending = scope.borrow().parent.iter().chain(scope.borrow().closure.iter()).cloned().collect::<Vec<_>>();
+
+        while let Some(env) = pending.pop() {
+            if outer.iter().any(|known| Rc::ptr_eq(known, &env)) {
+                continue;
+            }
+
+            pending.extend(env.borrow().parent.iter().chain(env.borrow().closure.iter()).cloned());
+            outer.push(env);
         }
+
+        let mut scopes: Vec<Rc<RefCell<Environment>>> = vec![scope.clone()];
+        let mut edges: Vec<Vec<usize>> = Vec::new();
+        let mut index = 0;
+
+        while index < scopes.len() {
+            let mut targets: Vec<usize> = Vec::new();
+
+            let links = scopes[index].borrow().links();
+
+            for link in links {
+                if outer.iter().any(|known| Rc::ptr_eq(known, &link)) {
+                    continue;
+                }
+
+                let target = match scopes.iter().position(|known| Rc::ptr_eq(known, &link)) {
+                    Some(target) => target,
+                    None => {
+                        scopes.push(link);
+                        scopes.len() - 1
+                    }
+                };
+
+                targets.push(target);
+            }
+
+            edges.push(targets);
+            index += 1;
+        }
+
+        drop(outer);
+
+        // Handles that do not come from the scopes found here: the list above, `scope` itself and anything outside
+        let mut internal = vec![1; scopes.len()];
+        internal[0] += 1;
+
+        for &target in edges.iter().flatten() {
+            internal[target] += 1;
+        }
+
+        let mut reachable: Vec<usize> = (0..scopes.len())
+            .filter(|&i| Rc::strong_count(&scopes[i]) > internal[i])
+            .collect();
+        let mut alive = vec![false; scopes.len()];
+
+        while let Some(i) = reachable.pop() {
+            if !alive[i] {
+                alive[i] = true;
+                reachable.extend(&edges[i]);
+            }
+        }
+
+        if alive[0] {
+            return;
+        }
+
+        let mut dropped: Vec<HashMap<u64, VariableSlot>> = Vec::new();
+
+        for (env, alive) in scopes.iter().zip(alive) {
+            if !alive {
+                dropped.push(std::mem::take(&mut env.borrow_mut().references));
+            }
+        }
+
+        drop(scopes);
+        drop(dropped);
+    }
+
+    // The next environment to search for a reference that is not stored here
+    fn linked(&self, reference: u64) -> Option<Rc<RefCell<Environment>>> {
+        if let Some(closure) = &self.closure
+            && closure.borrow().variable_exists(reference) {
+            return Some(closure.clone());
+        }
+
+        self.parent.clone()
     }
 
     fn next_reference(&self) -> u64 {
         GLOBAL_REFERENCE_COUNT.fetch_add(1, Ordering::SeqCst)
     }
 
+    // A function stored in the scope it captured only holds that scope weakly, see `Capture`
+    fn store(&mut self, reference: u64, mut value: Value) {
+        if let ValueType::Function { capture, .. } = value.get_type_mut()
+            && capture.is(self) {
+            *capture = capture.downgrade();
+        }
+
+        self.references.insert(reference, VariableSlot::Variable(value));
+    }
+
+    fn load(value: &Value) -> Value {
+        let mut value = value.clone();
+
+        if let ValueType::Function { capture, .. } = value.get_type_mut() {
+            *capture = capture.upgrade();
+        }
+
+        value
+    }
+
     fn create_value(&mut self, name: String, mut value: Value) {
         let reference = self.next_reference();
         
         value.set_reference(reference);
-        self.references.insert(reference, VariableSlot::Variable(value));
+        self.store(reference, value);
         self.values.insert(name, reference);
     }
 
@@ -85,11 +233,31 @@ impl Environment {
         let reference = self.next_reference();
         
         value.set_reference(reference);
-        self.references.insert(reference, VariableSlot::Variable(value));
+        self.store(reference, value);
 
         reference
     }
 
+    // Stores the value with its children in the same environment that owns `owner`, so they live as long as the owner does
+    pub fn create_value_beside(&mut self, owner: u64, value: Value, children: Vec<Value>) -> u64 {
+        let parent_owner = match self.references.get(&owner) {
+            Some(VariableSlot::Variable(_)) => None,
+            Some(VariableSlot::Reference(parent_reference)) => Some(*parent_reference),
+            None => Some(owner)
+        };
+
+        match (parent_owner, self.parent.clone()) {
+            (Some(parent_owner), Some(parent)) => parent.borrow_mut().create_value_beside(parent_owner, value, children),
+            _ => {
+                for child in children {
+                    self.create_by_value(child);
+                }
+
+                self.create_value_without_name(value)
+            }
+        }
+    }
+
     pub fn create_value_reference(&mut self, name: String, reference: u64) {        
         let next_reference = self.next_reference();
         
@@ -108,6 +276,11 @@ impl Environment {
             return true;
         }
 
+        if let Some(closure) = &self.closure
+            && closure.borrow().variable_exists(reference) {
+            return true;
+        }
+
         if let Some(parent) = &self.parent {
             return parent.borrow().variable_exists(reference);
         }
@@ -120,6 +293,11 @@ impl Environment {
             return true;
         }
 
+        if let Some(closure) = &self.closure
+            && closure.borrow().variable_is_used(reference) {
+            return true;
+        }
+
         if let Some(parent) = &self.parent {
             return parent.borrow().variable_is_used(reference);
         }
@@ -150,12 +328,60 @@ impl Environment {
         }
     }
 
+    pub fn add_implementation(&mut self, interface: String, data_type: DataType) {
+        self.implementations.push((interface, data_type));
+    }
+
+    pub fn implements(&self, interface: &str, data_type: &DataType) -> bool {
+        if self.implementations.iter().any(|(name, implementor)| name == interface && implementor == data_type) {
+            return true;
+        }
+
+        if let Some(closure) = &self.closure
+            && closure.borrow().implements(interface, data_type) {
+            return true;
+        }
+
+        match &self.parent {
+            Some(parent) => parent.borrow().implements(interface, data_type),
+            None => false
+        }
+    }
+
+    pub fn mark_constant(&mut self, reference: u64, name: &str) {
+        match self.references.get(&reference) {
+            Some(VariableSlot::Variable(_)) => {
+                self.constants.insert(reference, name.to_string());
+            },
+            Some(VariableSlot::Reference(parent_reference)) => {
+                let parent_reference = *parent_reference;
+
+                if let Some(parent) = self.linked(parent_reference) {
+                    parent.borrow_mut().mark_constant(parent_reference, name);
+                }
+            },
+            None => {
+                if let Some(parent) = self.linked(reference) {
+                    parent.borrow_mut().mark_constant(reference, name);
+                }
+            }
+        }
+    }
+
+    fn check_constant(&self, reference: u64) -> io::Result<()> {
+        match self.constants.get(&reference) {
+            Some(name) => Err(io::Error::new(io::ErrorKind::InvalidData, format!("Cannot assign to constant {}!", name))),
+            None => Ok(())
+        }
+    }
+
     pub fn assign_variable_on_reference(&mut self, reference: u64, mut value: Value) -> io::Result<()> {
         if let Some(slot) = self.references.get(&reference) {
             match slot.clone() {
-                VariableSlot::Variable(_) => {                    
+                VariableSlot::Variable(_) => {
+                    self.check_constant(reference)?;
                     value.set_reference(reference);
-                    self.references.insert(reference, VariableSlot::Variable(value.clone()));
+                    self.store(reference, value);
                 }
 
                 VariableSlot::Reference(parent_reference) => {
@@ -163,7 +389,7 @@ impl Environment {
                 }
             }
         } else {
-            if let Some(parent) = self.parent.clone() {
+            if let Some(parent) = self.linked(reference) {
                 let mut parent = parent.borrow_mut();
 
                 parent.assign_variable_on_reference(reference, value)?;
@@ -175,24 +401,29 @@ impl Environment {
 
     pub fn assign_variable(&mut self, name: &str, value: Value) -> io::Result<()> {
         let expected = self.get_variable(name)?;
+        let expected_type = expected.get_data_type();
 
-        if expected.get_type().get_data_type() != value.get_type().get_data_type() {
+        // Only the declaration knows whether a null variable is allowed to hold a value, the analyzer checks that
+        if DataType::common(&expected_type, &value.get_data_type()).is_none() {
             return Err(io::Error::new(io::ErrorKind::InvalidData, "Invalid type for assignment!"));
         }
 
         if let Some(&reference) = self.values.get(name) {
             if let Some(slot) = self.references.get(&reference) {
                 if let VariableSlot::Reference(parent_reference) = slot {
-                    if let Some(parent) = &self.parent {
+                    if let Some(parent) = self.linked(*parent_reference) {
                         return parent.borrow_mut().assign_variable_on_reference(parent_reference.clone(), value);
                     } else {
                         return Err(io::Error::new(io::ErrorKind::InvalidData, format!("")))
                     }
                 }
             }
-            
-            self.references.insert(reference, VariableSlot::Variable(Value::new(Some(reference), value.get_type().clone())));
+
+            self.check_constant(reference)?;
+            self.store(reference, Value::new(Some(reference), value.get_type().clone()));
             Ok(())
+        } else if let Some(closure) = &self.closure {
+            closure.borrow_mut().assign_variable(name, value)
         } else if let Some(parent) = &self.parent {
             parent.borrow_mut().assign_variable(name, value)
         } else {
@@ -204,23 +435,94 @@ impl Environment {
         if let Some(slot) = self.references.get(&reference) {
             match slot {
                 VariableSlot::Variable(value) => {
-                    return Ok(value.clone());
+                    return Ok(Environment::load(value));
                 }
                 VariableSlot::Reference(parent_reference) => {
                     let parent_reference = parent_reference.clone();
                     
-                    if let Some(parent) = &self.parent {
+                    if let Some(parent) = self.linked(parent_reference) {
                         return parent.borrow().get_by_reference(parent_reference);
                     }
                 }
             }
-        } else if let Some(parent) = &self.parent {
+        } else if let Some(parent) = self.linked(reference) {
             return parent.borrow().get_by_reference(reference);
         }
 
         return Err(io::Error::new(io::ErrorKind::InvalidData, format!("Reference not found {}!", reference)));
     }
 
+    pub fn value_to_string(&self, value: &Value) -> io::Result<String> {
+        Self::format_value(value, &|reference| self.get_by_reference(reference), &mut |_| Ok(None))
+    }
+
+    // The printed form of a value, `get` looks up the items of collections and `display` can replace
+    // the form of the value or of any item inside it
+    pub fn format_value(
+        value: &Value,
+        get: &dyn Fn(u64) -> io::Result<Value>,
+        display: &mut dyn FnMut(&Value) -> io::Result<Option<String>>
+    ) -> io::Result<String> {
+        if let Some(displayed) = display(value)? {
+            return Ok(displayed);
+        }
+
+        match value.get_type() {
+            ValueType::Integer(number) => Ok(number.to_string()),
+            ValueType::Float(number) => Ok(number.to_string()),
+            ValueType::Boolean(boolean) => Ok(boolean.to_string()),
+            ValueType::String(string) => Ok(string.clone()),
+            ValueType::Null => Ok(String::from("null")),
+            ValueType::List { references, data_type: _ } => {
+                Ok(format!("[{}]", Self::references_to_string(references, get, display)?))
+            },
+            ValueType::Tuple { references, data_types: _ } => {
+                Ok(format!("({})", Self::references_to_string(references, get, display)?))
+            },
+            ValueType::Map { references, key_type: _, value_type: _ } => {
+                let mut entries: Vec<String> = Vec::new();
+
+                for (key, reference) in references {
+                    let key = Self::format_value(&Value::new(None, key.to_value_type()), get, display)?;
+                    let value = get(*reference)?;
+
+                    entries.push(format!("{}: {}", key, Self::format_value(&value, get, display)?));
+                }
+
+                Ok(format!("{{{}}}", entries.join(", ")))
+            },
+            ValueType::Set { values, data_type: _ } => {
+                let mut children: Vec<String> = Vec::new();
+
+                for value in values {
+                    children.push(Self::format_value(&Value::new(None, value.to_value_type()), get, display)?);
+                }
+
+                Ok(format!("{{{}}}", children.join(", ")))
+            },
+            ValueType::Range(range) => Ok(range.to_string()),
+            value_type => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Unsupported value to print: {}", value_type.get_data_type())
+            ))
+        }
+    }
+
+    fn references_to_string(
+        references: &[u64],
+        get: &dyn Fn(u64) -> io::Result<Value>,
+        display: &mut dyn FnMut(&Value) -> io::Result<Option<String>>
+    ) -> io::Result<String> {
+        let mut children: Vec<String> = Vec::new();
+
+        for reference in references {
+            let child = get(*reference)?;
+            children.push(Self::format_value(&child, get, display)?);
+        }
+
+        Ok(children.join(", "))
+    }
+
     pub fn display_references(&self) {
         println!("==========[Values]==========");
         
@@ -250,7 +552,7 @@ impl Environment {
 
     pub fn create_by_value(&mut self, value: Value) {
         if let Some(reference) = value.get_reference() {
-            self.references.insert(reference, VariableSlot::Variable(value));
+            self.store(reference, value);
         }
     }
 
@@ -267,13 +569,13 @@ impl Environment {
             if let Some(slot) = self.references.get(reference) {
                 match slot {
                     VariableSlot::Variable(value) => {
-                        return Ok(value.clone());
+                        return Ok(Environment::load(value));
                     }
 
                     VariableSlot::Reference(parent_reference) => {
                         let parent_reference = parent_reference.clone();
                         
-                        if let Some(parent) = &self.parent {                            
+                        if let Some(parent) = self.linked(parent_reference) {                            
                             return parent.borrow().get_by_reference(parent_reference);
                         } 
 
@@ -283,6 +585,10 @@ impl Environment {
                 }
             }
         }
+
+        if let Some(closure) = &self.closure {
+            return closure.borrow().get_variable(name)
+        }
         
         if let Some(parent) = &self.parent {
             return parent.borrow().get_variable(name)
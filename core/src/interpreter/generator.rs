@@ -0,0 +1,257 @@
+use std::cell::RefCell;
+use std::fmt;
+use std::io;
+use std::rc::Rc;
+
+use crate::parser::expression::Expression;
+use crate::parser::pattern::Pattern;
+use crate::parser::statement::Statement;
+
+use super::enviroment::Environment;
+use super::interpret_statement::InterpretStatement;
+use super::interpreter::Interpreter;
+use super::iteration::Iteration;
+use super::return_value::Return;
+use super::value::{Value, ValueType};
+
+
+// Where a suspended generator continues, the innermost block is the last frame
+enum Frame {
+    Block {
+        body: Vec<Statement>,
+        index: usize,
+        // The block entered its own environment and leaves it when it ends
+        scoped: bool
+    },
+    While {
+        condition: Expression,
+        body: Vec<Statement>
+    },
+    For {
+        pattern: Pattern,
+        iteration: Iteration,
+        body: Vec<Statement>
+    },
+    Try {
+        name: String,
+        catch_body: Vec<Statement>,
+        local: Rc<RefCell<Environment>>
+    }
+}
+
+enum Step {
+    Continue,
+    Yield(Value),
+    Done
+}
+
+// A running generator function, every call runs its body up to the next `yield`
+pub struct Generator {
+    frames: Vec<Frame>,
+    local: Rc<RefCell<Environment>>,
+    source_file: String
+}
+
+impl Generator {
+    pub fn new(body: Vec<Statement>, local: Rc<RefCell<Environment>>, source_file: String) -> Generator {
+        Generator {
+            frames: vec![Frame::Block { body, index: 0, scoped: false }],
+            local,
+            source_file
+        }
+    }
+
+    // The next yielded value, or `None` once the body has finished
+    pub fn resume(&mut self, interpreter: &mut Interpreter) -> io::Result<Option<Value>> {
+        let caller = interpreter.get_local();
+        let caller_file = std::mem::replace(&mut interpreter.source_file, self.source_file.clone());
+        interpreter.restore_enviroment(self.local.clone());
+
+        let mut result = self.run(interpreter);
+
+        // The caller cannot see the scopes of the generator, so a yielded collection brings its elements along
+        let mut children: Vec<Value> = Vec::new();
+
+        if let Ok(Some(value)) = &result
+            && let Err(error) = interpreter.collect_children(value, &mut children) {
+            result = Err(error);
+        }
+
+        self.local = interpreter.get_local();
+        interpreter.restore_enviroment(caller.clone());
+        interpreter.source_file = caller_file;
+
+        for child in children {
+            caller.borrow_mut().create_by_value(child);
+        }
+
+        if let Ok(Some(value)) = &mut result
+            && let Some(reference) = value.get_reference()
+            && !interpreter.variable_exists(reference) {
+            value.clear_reference();
+        }
+
+        if !matches!(result, Ok(Some(_))) {
+            self.frames.clear();
+        }
+
+        result
+    }
+
+    fn run(&mut self, interpreter: &mut Interpreter) -> io::Result<Option<Value>> {
+        loop {
+            match self.step(interpreter) {
+                Ok(Step::Continue) => {},
+                Ok(Step::Yield(value)) => return Ok(Some(value)),
+                Ok(Step::Done) => return Ok(None),
+                Err(error) => self.catch(interpreter, error)?
+            }
+        }
+    }
+
+    fn step(&mut self, interpreter: &mut Interpreter) -> io::Result<Step> {
+        let Some(frame) = self.frames.last_mut() else {
+            return Ok(Step::Done);
+        };
+
+        match frame {
+            Frame::Block { body, index, scoped } => {
+                let Some(statement) = body.get(*index).cloned() else {
+                    if *scoped {
+                        interpreter.exit_enviroment()?;
+                    }
+
+                    self.frames.pop();
+                    return Ok(Step::Continue);
+                };
+
+                *index += 1;
+                self.execute(interpreter, statement)
+            },
+            Frame::While { condition, body } => {
+                let body = body.clone();
+
+                if Generator::condition(interpreter, condition.clone(), "while")? {
+                    interpreter.enter_enviroment();
+                    self.frames.push(Frame::Block { body, index: 0, scoped: true });
+                } else {
+                    self.frames.pop();
+                }
+
+                Ok(Step::Continue)
+            },
+            Frame::For { pattern, iteration, body } => {
+                interpreter.enter_enviroment();
+
+                match iteration.next(interpreter)? {
+                    Some(item) => {
+                        let pattern = pattern.clone();
+                        let body = body.clone();
+
+                        InterpretStatement::new(interpreter).define_pattern(&pattern, item)?;
+                        self.frames.push(Frame::Block { body, index: 0, scoped: true });
+                    },
+                    None => {
+                        interpreter.exit_enviroment()?;
+                        self.frames.pop();
+                    }
+                }
+
+                Ok(Step::Continue)
+            },
+            // The body above it finished without an error
+            Frame::Try { .. } => {
+                self.frames.pop();
+                Ok(Step::Continue)
+            }
+        }
+    }
+
+    // Statements without a `yield` run to completion, the others become frames
+    fn execute(&mut self, interpreter: &mut Interpreter, statement: Statement) -> io::Result<Step> {
+        if !statement.contains_yield() {
+            return match InterpretStatement::new(interpreter).interpret_statement(statement)? {
+                Return::Success(_) => Ok(Step::Done),
+                Return::Nothing => Ok(Step::Continue)
+            };
+        }
+
+        match statement {
+            Statement::Yield { value } => Ok(Step::Yield(interpreter.interpret_expression(value)?)),
+            Statement::IfStatement { condition, body, else_body } => {
+                let body = if Generator::condition(interpreter, condition, "if")? {
+                    Some(body)
+                } else {
+                    else_body
+                };
+
+                if let Some(body) = body {
+                    interpreter.enter_enviroment();
+                    self.frames.push(Frame::Block { body, index: 0, scoped: true });
+                }
+
+                Ok(Step::Continue)
+            },
+            Statement::WhileStatement { condition, body } => {
+                self.frames.push(Frame::While { condition, body });
+                Ok(Step::Continue)
+            },
+            Statement::ForLoopStatement { pattern, list, body } => {
+                let iterable = interpreter.interpret_expression(list)?;
+                let iteration = Iteration::from(&iterable)?;
+
+                self.frames.push(Frame::For { pattern, iteration, body });
+                Ok(Step::Continue)
+            },
+            Statement::TryStatement { body, name, catch_body } => {
+                self.frames.push(Frame::Try { name, catch_body, local: interpreter.get_local() });
+
+                interpreter.enter_enviroment();
+                self.frames.push(Frame::Block { body, index: 0, scoped: true });
+
+                Ok(Step::Continue)
+            },
+            _ => Err(io::Error::new(io::ErrorKind::InvalidData, "Unsupported statement in generator!"))
+        }
+    }
+
+    // An error unwinds to the innermost `try` of the generator, without one it ends the generator
+    fn catch(&mut self, interpreter: &mut Interpreter, error: io::Error) -> io::Result<()> {
+        let Some(position) = self.frames.iter().rposition(|frame| matches!(frame, Frame::Try { .. })) else {
+            return Err(error);
+        };
+
+        let Some(Frame::Try { name, catch_body, local }) = self.frames.drain(position..).next() else {
+            unreachable!()
+        };
+
+        interpreter.restore_enviroment(local);
+        interpreter.source_file = self.source_file.clone();
+
+        interpreter.enter_enviroment();
+        interpreter.define_variable(&name, Value::new(None, ValueType::String(error.to_string())))?;
+        self.frames.push(Frame::Block { body: catch_body, index: 0, scoped: true });
+
+        Ok(())
+    }
+
+    fn condition(interpreter: &mut Interpreter, condition: Expression, statement: &str) -> io::Result<bool> {
+        match interpreter.interpret_expression(condition)?.get_type() {
+            ValueType::Boolean(boolean) => Ok(*boolean),
+            _ => Err(io::Error::new(io::ErrorKind::InvalidData, format!("Not boolean type in {} condition", statement)))
+        }
+    }
+}
+
+impl fmt::Debug for Generator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Generator({} frames)", self.frames.len())
+    }
+}
+
+// Two generator values are equal when they share the same running body
+impl PartialEq for Generator {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::eq(self, other)
+    }
+}
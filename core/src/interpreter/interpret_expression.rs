@@ -1,14 +1,54 @@
-use std::cell::RefCell;
+use std::collections::{BTreeMap, BTreeSet};
 use std::io;
-use std::rc::Rc;
 
-use crate::interpreter::enviroment::Environment;
-use crate::parser::expression::Expression;
+use crate::parser::expression::{Expression, FormatPart};
 use crate::parser::operator::Operator;
 use crate::parser::data_type::DataType;
 
+use super::capture::Capture;
 use super::interpreter::Interpreter;
-use super::value::{Value, ValueType};
+use super::range::Range;
+use super::value::{MapKey, Value, ValueType};
+
+pub fn checked_integer(result: Option<i64>, left: i64, operator: &str, right: i64) -> io::Result<i64> {
+    result.ok_or_else(|| io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("Integer overflow: {} {} {} does not fit into int!", left, operator, right)
+    ))
+}
+
+// Negative indices count from the end, `-1` is the last element
+pub fn resolve_index(index: i64, length: usize, collection: &str) -> io::Result<usize> {
+    let resolved = if index < 0 { index + length as i64 } else { index };
+
+    if resolved < 0 || resolved >= length as i64 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Index {} is out of range for a {} of length {}!", index, collection, length)
+        ));
+    }
+
+    Ok(resolved as usize)
+}
+
+// Bounds of `[start:end]` as positions, a bound may sit right after the last element
+fn resolve_slice(start: Option<i64>, end: Option<i64>, length: usize, collection: &str) -> io::Result<(usize, usize)> {
+    let resolve = |bound: i64| if bound < 0 { bound + length as i64 } else { bound };
+
+    let from = start.map_or(0, resolve);
+    let to = end.map_or(length as i64, resolve);
+
+    if from < 0 || to > length as i64 || from > to {
+        let bound = |bound: Option<i64>| bound.map(|bound| bound.to_string()).unwrap_or_default();
+
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Slice [{}:{}] is out of range for a {} of length {}!", bound(start), bound(end), collection, length)
+        ));
+    }
+
+    Ok((from as usize, to as usize))
+}
 
 pub struct InterpretExpression<'a> {
     interpreter: &'a mut Interpreter
@@ -22,10 +62,27 @@ impl<'a> InterpretExpression<'a> {
 
     pub fn interpret_expression(&mut self, expression: Expression) -> io::Result<Value> {        
         match expression {
+            Expression::BinaryOp { left, operator: Operator::NullCoalescing, right } => {
+                let left_value = self.interpret_expression(*left)?;
+
+                if let ValueType::Null = left_value.get_type() {
+                    self.interpret_expression(*right)
+                } else {
+                    Ok(left_value)
+                }
+            },
             Expression::BinaryOp { left, operator, right } => {
                 let left_value = self.interpret_expression(*left)?;
-                let left_value = left_value.get_type().clone();
                 let right_value  = self.interpret_expression(*right)?;
+
+                if (operator == Operator::EqualEqual || operator == Operator::NotEqual)
+                    && left_value.get_data_type() == right_value.get_data_type() {
+                    let equal = self.interpreter.values_equal(&left_value, &right_value)?;
+
+                    return Ok(Value::new(None, ValueType::Boolean(equal == (operator == Operator::EqualEqual))));
+                }
+
+                let left_value = left_value.get_type().clone();
                 let right_value = right_value.get_type().clone();
 
                 let value_type = self.interpret_binary_operation(left_value, right_value, operator)?;
@@ -47,7 +104,10 @@ impl<'a> InterpretExpression<'a> {
 
                 self.interpret_front_unary_operation(value, operator)
             },
-            Expression::FunctionCall(name, parameters) => {
+            Expression::FunctionCall(callee, parameters) => {
+                let name = Expression::callee_name(&callee);
+                let function = self.interpret_expression(*callee)?;
+
                 let mut args: Vec<Value> = Vec::new();
 
                 for parameter in parameters {
@@ -55,7 +115,28 @@ impl<'a> InterpretExpression<'a> {
                     args.push(value);
                 }
 
-                self.interpreter.call_function(&name, args)
+                self.interpreter.call_value(&name, &function, args)
+            },
+            Expression::SwappedCall { method, left, right } => {
+                let left = self.interpret_expression(*left)?;
+                let right = self.interpret_expression(*right)?;
+                let function = self.interpreter.get_variable(&method)?;
+
+                self.interpreter.call_value(&method, &function, vec![right, left])
+            },
+            Expression::MethodCall { receiver, name, arguments } => {
+                let receiver = self.interpret_expression(*receiver)?;
+                let method = receiver.get_data_type().method_name(&name);
+                let function = self.interpreter.get_variable(&method)?;
+
+                let mut args: Vec<Value> = vec![receiver];
+
+                for argument in arguments {
+                    let value = self.interpret_expression(argument)?;
+                    args.push(value);
+                }
+
+                self.interpreter.call_value(&method, &function, args)
             },
             Expression::ListLiteral(expressions) => {
                 let mut references: Vec<u64> = Vec::new();
@@ -63,13 +144,16 @@ impl<'a> InterpretExpression<'a> {
 
                 for (i, expression) in expressions.iter().enumerate() {
                     let expression = expression.clone();
+                    let upcast = Expression::upcast_type(&expression);
                     let value = self.interpret_expression(expression)?;
+                    let value_type = upcast.unwrap_or_else(|| value.get_data_type());
 
                     if i == 0 {
-                        data_type = value.get_type().get_data_type();
+                        data_type = value_type;
                     } else {
-                        if value.get_type().get_data_type() != data_type.clone() {
-                            return Err(io::Error::new(io::ErrorKind::InvalidData, "List type mismatch!"))
+                        match DataType::common(&data_type, &value_type) {
+                            Some(common) => data_type = common,
+                            None => return Err(io::Error::new(io::ErrorKind::InvalidData, "List type mismatch!"))
                         }
                     }
 
@@ -121,12 +205,72 @@ impl<'a> InterpretExpression<'a> {
 
                 Ok(Value::new(None, ValueType::Tuple { references: references, data_types: DataType::Tuple(data_types) }))
             },
-            Expression::TupleIndex { left, indeces } => {
+            Expression::MapLiteral(entries) => {
+                let mut references: BTreeMap<MapKey, u64> = BTreeMap::new();
+                let mut key_type: DataType = DataType::void();
+                let mut value_type: DataType = DataType::void();
+
+                for (i, (key, value)) in entries.into_iter().enumerate() {
+                    let upcast = Expression::upcast_type(&value);
+                    let key = self.interpret_expression(key)?;
+                    let value = self.interpret_expression(value)?;
+                    let entry_type = upcast.unwrap_or_else(|| value.get_data_type());
+
+                    if i == 0 {
+                        key_type = key.get_data_type();
+                        value_type = entry_type;
+                    } else {
+                        match DataType::common(&value_type, &entry_type) {
+                            Some(common) if key.get_data_type() == key_type => value_type = common,
+                            _ => return Err(io::Error::new(io::ErrorKind::InvalidData, "Map type mismatch!"))
+                        }
+                    }
+
+                    let key = MapKey::from_value_type(key.get_type())?;
+                    let reference = self.interpreter.child_reference(value);
+
+                    references.insert(key, reference);
+                }
+
+                Ok(Value::new(None, ValueType::Map { references, key_type, value_type }))
+            },
+            Expression::SetLiteral(expressions) => {
+                let mut values: BTreeSet<MapKey> = BTreeSet::new();
+                let mut data_type: DataType = DataType::void();
+
+                for (i, expression) in expressions.into_iter().enumerate() {
+                    let value = self.interpret_expression(expression)?;
+
+                    if i == 0 {
+                        data_type = value.get_data_type();
+                    } else if value.get_data_type() != data_type {
+                        return Err(io::Error::new(io::ErrorKind::InvalidData, "Set type mismatch!"));
+                    }
+
+                    values.insert(MapKey::from_value_type(value.get_type())?);
+                }
+
+                Ok(Value::new(None, ValueType::Set { values, data_type }))
+            },
+            Expression::EmptyCollection(data_type) => {
+                let value_type = match data_type {
+                    DataType::List(data_type) => ValueType::List { references: Vec::new(), data_type: *data_type },
+                    DataType::Map(key_type, value_type) => ValueType::Map { references: BTreeMap::new(), key_type: *key_type, value_type: *value_type },
+                    DataType::Set(data_type) => ValueType::Set { values: BTreeSet::new(), data_type: *data_type },
+                    _ => return Err(io::Error::new(io::ErrorKind::InvalidData, "Invalid empty collection type!"))
+                };
+
+                Ok(Value::new(None, value_type))
+            },
+            Expression::TupleIndex { left, indeces, null_safe } => {
                 let mut value = self.interpret_expression(*left)?;
+
+                if null_safe && *value.get_type() == ValueType::Null {
+                    return Ok(value);
+                }
                 
                 
                 for index in indeces {
-                    let index = index as usize;
                     let value_type = value.get_type();
                     
                     if let ValueType::Tuple { references, data_types: _} = value_type {
@@ -150,20 +294,22 @@ impl<'a> InterpretExpression<'a> {
                 let value = self.interpret_identifier_index(left.get_type().clone(), index.get_type().clone())?;
                 Ok(value)
             },
+            Expression::Slice { left, start, end } => {
+                let left = self.interpret_expression(*left)?;
+                let start = start.map(|start| self.interpret_integer(*start)).transpose()?;
+                let end = end.map(|end| self.interpret_integer(*end)).transpose()?;
+
+                self.interpret_slice(left.get_type().clone(), start, end)
+            },
             Expression::FunctionLiteral { parameters, return_type, block } => {
-                let mut capture = Environment::new();
-                {
-                    let local = self.interpreter.get_local();
-                    let local = local.borrow();
+                // Shared, not copied, so the function sees later updates of the variables around it
+                let capture = Capture::Strong(self.interpreter.get_local());
 
-                    capture = local.partially_clone();
-                }
-                
                 Ok(Value::new(None, ValueType::Function { 
                     return_type, 
                     parameters, 
                     body: block,
-                    capture: Rc::new(RefCell::new(capture))
+                    capture
                 }))
             },
             Expression::IntegerLiteral(value) => {
@@ -186,38 +332,133 @@ impl<'a> InterpretExpression<'a> {
                 
                 Ok(value)
             },
+            Expression::FormatString(parts) => {
+                let mut string = String::new();
+
+                for part in parts {
+                    match part {
+                        FormatPart::Literal(literal) => string.push_str(&literal),
+                        FormatPart::Expression(expression) => {
+                            let value = self.interpret_expression(expression)?;
+
+                            string.push_str(&self.interpreter.value_to_string(&value)?);
+                        }
+                    }
+                }
+
+                Ok(Value::new(None, ValueType::String(string)))
+            },
             Expression::NullLiteral => {
                 let value = Value::new(None, ValueType::Null);
 
                 Ok(value)
             },
+            Expression::Cast { expression, data_type } => {
+                let value = self.interpret_expression(*expression)?;
+                self.interpret_cast(value, &data_type)
+            },
+            Expression::Range { start, end, step, inclusive } => {
+                let start = self.interpret_integer(*start)?;
+                let end = self.interpret_integer(*end)?;
+                let step = match step {
+                    Some(step) => self.interpret_integer(*step)?,
+                    None => 1
+                };
+
+                Ok(Value::new(None, ValueType::Range(Range::new(start, end, step, inclusive)?)))
+            },
+            Expression::Parse { expression, data_type } => {
+                let value = self.interpret_expression(*expression)?;
+                self.interpret_parse(value, &data_type)
+            },
             Expression::Identifier(name) => {
                 self.interpreter.get_variable(name.as_str())
             }
         }
     }    
 
+    fn interpret_integer(&mut self, expression: Expression) -> io::Result<i64> {
+        match self.interpret_expression(expression)?.get_type() {
+            ValueType::Integer(number) => Ok(*number),
+            other => Err(io::Error::new(io::ErrorKind::InvalidData, format!("Expected int, got {}!", other.get_data_type())))
+        }
+    }
+
+    fn interpret_cast(&mut self, value: Value, data_type: &DataType) -> io::Result<Value> {
+        // Values keep their own type behind an interface, so methods dispatch on it
+        if let DataType::Interface(_) = data_type {
+            return Ok(value);
+        }
+
+        if *data_type == DataType::String {
+            return self.interpreter.display_value(value);
+        }
+
+        let value_type = match (value.get_type(), data_type) {
+            (ValueType::Integer(number), DataType::Float) => ValueType::Float(*number as f64),
+            (ValueType::Float(number), DataType::Int) => {
+                if number.is_nan() {
+                    return Err(io::Error::new(io::ErrorKind::InvalidData, "Cannot cast NaN to int!"));
+                }
+
+                // Truncates toward zero, the bounds are the exact float values of i64::MIN and i64::MAX + 1
+                if *number < -9_223_372_036_854_775_808.0 || *number >= 9_223_372_036_854_775_808.0 {
+                    return Err(io::Error::new(io::ErrorKind::InvalidData, format!("Cannot cast {} to int, it is out of range!", number)));
+                }
+
+                ValueType::Integer(number.trunc() as i64)
+            },
+            (ValueType::Boolean(boolean), DataType::Int) => ValueType::Integer(*boolean as i64),
+            (value_type, data_type) if value_type.get_data_type() == *data_type => value_type.clone(),
+            (value_type, data_type) => return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Cannot cast {} to {}!", value_type.get_data_type(), data_type)
+            ))
+        };
+
+        Ok(Value::new(None, value_type))
+    }
+
+    fn interpret_parse(&self, value: Value, data_type: &DataType) -> io::Result<Value> {
+        let string = match value.get_type() {
+            ValueType::String(string) => string,
+            value_type => return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{}() expects a string, got {}!", data_type, value_type.get_data_type())
+            ))
+        };
+
+        let value_type = match data_type {
+            DataType::Int => string.parse::<i64>().map(ValueType::Integer).ok(),
+            DataType::Float => string.parse::<f64>().map(ValueType::Float).ok(),
+            _ => None
+        };
+
+        Ok(Value::new(None, value_type.unwrap_or(ValueType::Null)))
+    }
+
     pub fn interpret_identifier_index(&self, left: ValueType, index: ValueType) -> io::Result<Value> {
+        if let ValueType::Map { references, key_type: _, value_type: _ } = &left {
+            let key = MapKey::from_value_type(&index)?;
+
+            return match references.get(&key) {
+                Some(reference) => self.interpreter.get_variable_reference(*reference),
+                None => Err(io::Error::new(io::ErrorKind::InvalidData, format!("Key {} not found in map!", key)))
+            };
+        }
+
         if let ValueType::Integer(index) = index {
             match left {
                 ValueType::String(str) => {
-                    let character = str.chars().nth(index as usize);
-                    if let Some(character) = character {
-                        let value_type = ValueType::String(character.to_string());
-    
-                        Ok(Value::new(None, value_type))
-                    } else {
-                        Err(io::Error::new(io::ErrorKind::InvalidData, "Out of bounds in string."))
-                    }
+                    let characters: Vec<char> = str.chars().collect();
+                    let index = resolve_index(index, characters.len(), "string")?;
+
+                    Ok(Value::new(None, ValueType::String(characters[index].to_string())))
                 },
                 ValueType::List { references, data_type: _ } => {
-                    let child_reference = references.iter().nth(index as usize);
-                    
-                    if let Some(child_reference) = child_reference {
-                        self.interpreter.get_variable_reference(child_reference.clone())
-                    } else {
-                        Err(io::Error::new(io::ErrorKind::InvalidData, "Out of bounds!"))
-                    }
+                    let index = resolve_index(index, references.len(), "list")?;
+
+                    self.interpreter.get_variable_reference(references[index])
                 },
                 _ => Err(io::Error::new(
                     io::ErrorKind::InvalidData, 
@@ -232,6 +473,35 @@ impl<'a> InterpretExpression<'a> {
         }        
     }
 
+    // A slice is a new list, its elements are copies of the sliced ones
+    fn interpret_slice(&mut self, left: ValueType, start: Option<i64>, end: Option<i64>) -> io::Result<Value> {
+        match left {
+            ValueType::String(str) => {
+                let characters: Vec<char> = str.chars().collect();
+                let (from, to) = resolve_slice(start, end, characters.len(), "string")?;
+
+                Ok(Value::new(None, ValueType::String(characters[from..to].iter().collect())))
+            },
+            ValueType::List { references, data_type } => {
+                let (from, to) = resolve_slice(start, end, references.len(), "list")?;
+                let mut slice: Vec<u64> = Vec::new();
+
+                for reference in &references[from..to] {
+                    let mut child = self.interpreter.get_variable_reference(*reference)?;
+                    child.clear_reference();
+
+                    slice.push(self.interpreter.create_value(child));
+                }
+
+                Ok(Value::new(None, ValueType::List { references: slice, data_type }))
+            },
+            left => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Cannot slice {}, only lists and strings!", left.get_data_type())
+            ))
+        }
+    }
+
     fn interpret_front_unary_operation(&mut self, value: Value, operator: Operator) -> io::Result<Value> {
         match operator {
             Operator::PlusPlus => {
@@ -257,7 +527,7 @@ impl<'a> InterpretExpression<'a> {
         let value_type = value.get_type();
 
         let new_value_type = match value_type {
-            ValueType::Integer(number) => ValueType::Integer(number + 1),
+            ValueType::Integer(number) => ValueType::Integer(checked_integer(number.checked_add(1), *number, "+", 1)?),
             ValueType::Float(number) => ValueType::Float(number + 1.0),
             _ => {
                 return Err(io::Error::new(
@@ -279,7 +549,7 @@ impl<'a> InterpretExpression<'a> {
         let value_type = value.get_type();
 
         let new_value_type = match value_type {
-            ValueType::Integer(number) => ValueType::Integer(number - 1),
+            ValueType::Integer(number) => ValueType::Integer(checked_integer(number.checked_sub(1), *number, "-", 1)?),
             ValueType::Float(number) => ValueType::Float(number - 1.0),
             _ => {
                 return Err(io::Error::new(
@@ -311,6 +581,9 @@ impl<'a> InterpretExpression<'a> {
             Operator::NotEqual => self.interpret_tilde_equal(left, right),
             Operator::And => self.interpret_and(left, right),
             Operator::Or => self.interpret_or(left, right),
+            Operator::Union => self.interpret_union(left, right),
+            Operator::Intersection => self.interpret_intersection(left, right),
+            Operator::In => self.interpret_in(left, right),
             _ => Err(io::Error::new(io::ErrorKind::InvalidData, "Unsupported operator!"))
         }
     }
@@ -452,6 +725,11 @@ impl<'a> InterpretExpression<'a> {
 
                 Ok(value)
             },
+            (left, right) if left == ValueType::Null || right == ValueType::Null => {
+                let value = ValueType::Boolean(left != right);
+
+                Ok(value)
+            },
             _ => Err(io::Error::new(io::ErrorKind::InvalidData, "Different or unsupported data types!"))
         }
     }
@@ -478,6 +756,51 @@ impl<'a> InterpretExpression<'a> {
         }
     }
 
+    fn interpret_union(&self, left: ValueType, right: ValueType) -> io::Result<ValueType> {
+        match (left, right) {
+            (ValueType::Set { values: mut values1, data_type }, ValueType::Set { values: values2, data_type: _ }) => {
+                values1.extend(values2);
+
+                Ok(ValueType::Set { values: values1, data_type })
+            },
+            _ => Err(io::Error::new(io::ErrorKind::InvalidData, "Different or unsupported data types!"))
+        }
+    }
+
+    fn interpret_intersection(&self, left: ValueType, right: ValueType) -> io::Result<ValueType> {
+        match (left, right) {
+            (ValueType::Set { values: values1, data_type }, ValueType::Set { values: values2, data_type: _ }) => {
+                let values = values1.intersection(&values2).cloned().collect();
+
+                Ok(ValueType::Set { values, data_type })
+            },
+            _ => Err(io::Error::new(io::ErrorKind::InvalidData, "Different or unsupported data types!"))
+        }
+    }
+
+    fn interpret_in(&self, left: ValueType, right: ValueType) -> io::Result<ValueType> {
+        match (left, right) {
+            (ValueType::String(str1), ValueType::String(str2)) => Ok(ValueType::Boolean(str2.contains(&str1))),
+            (value, ValueType::List { references, data_type: _ }) => {
+                for reference in references {
+                    if *self.interpreter.get_variable_reference(reference)?.get_type() == value {
+                        return Ok(ValueType::Boolean(true));
+                    }
+                }
+
+                Ok(ValueType::Boolean(false))
+            },
+            (value, ValueType::Set { values, data_type: _ }) => {
+                Ok(ValueType::Boolean(values.contains(&MapKey::from_value_type(&value)?)))
+            },
+            (value, ValueType::Map { references, key_type: _, value_type: _ }) => {
+                Ok(ValueType::Boolean(references.contains_key(&MapKey::from_value_type(&value)?)))
+            },
+            (ValueType::Integer(number), ValueType::Range(range)) => Ok(ValueType::Boolean(range.contains(number))),
+            _ => Err(io::Error::new(io::ErrorKind::InvalidData, "Different or unsupported data types!"))
+        }
+    }
+
     fn interpret_unary_operation(&self, value: ValueType, operator: Operator) -> io::Result<ValueType> {
         match operator {
             Operator::Minus => {
@@ -500,7 +823,14 @@ impl<'a> InterpretExpression<'a> {
 
     fn interpret_negation(&self, value: ValueType) -> io::Result<ValueType> {
         match value {
-            ValueType::Integer(value) => Ok(ValueType::Integer(-value)),
+            ValueType::Integer(value) => {
+                let negated = value.checked_neg().ok_or_else(|| io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Integer overflow: -({}) does not fit into int!", value)
+                ))?;
+
+                Ok(ValueType::Integer(negated))
+            },
             ValueType::Float(value) => Ok(ValueType::Float(-value)),
             _ => Err(io::Error::new(io::ErrorKind::InvalidData, "Wrong type for not negation"))
         }
@@ -509,7 +839,7 @@ impl<'a> InterpretExpression<'a> {
     fn interpret_plus(&self, left: ValueType, right: ValueType) -> io::Result<ValueType> {
         match (left, right) {
             (ValueType::Integer(n1), ValueType::Integer(n2)) => {
-                let value = ValueType::Integer(n1 + n2);
+                let value = ValueType::Integer(checked_integer(n1.checked_add(n2), n1, "+", n2)?);
 
                 Ok(value)
             },
@@ -540,8 +870,13 @@ impl<'a> InterpretExpression<'a> {
 
     fn interpret_minus(&self, left: ValueType, right: ValueType) -> io::Result<ValueType> {
         match (left, right) {
+            (ValueType::Set { values: values1, data_type }, ValueType::Set { values: values2, data_type: _ }) => {
+                let values = values1.difference(&values2).cloned().collect();
+
+                Ok(ValueType::Set { values, data_type })
+            },
             (ValueType::Integer(n1), ValueType::Integer(n2)) => {
-                let value = ValueType::Integer(n1 - n2);
+                let value = ValueType::Integer(checked_integer(n1.checked_sub(n2), n1, "-", n2)?);
 
                 Ok(value)
             },
@@ -567,7 +902,7 @@ impl<'a> InterpretExpression<'a> {
     fn interpret_multiply(&self, left: ValueType, right: ValueType) -> io::Result<ValueType> {
         match (left, right) {
             (ValueType::Integer(n1), ValueType::Integer(n2)) => {
-                let value = ValueType::Integer(n1 * n2);
+                let value = ValueType::Integer(checked_integer(n1.checked_mul(n2), n1, "*", n2)?);
 
                 Ok(value)
             },
@@ -1,15 +1,17 @@
 use std::cell::RefCell;
-
 use std::io;
 use std::rc::Rc;
 
-use crate::interpreter::enviroment::Environment;
 use crate::parser::statement::Statement;
-use crate::parser::data_type::DataType;
+use crate::parser::pattern::Pattern;
 
+use super::capture::Capture;
+use super::generator::Generator;
+use super::interpret_expression::{checked_integer, resolve_index};
 use super::interpreter::Interpreter;
+use super::iteration::Iteration;
 use super::return_value::Return;
-use super::value::{Value, ValueType};
+use super::value::{MapKey, Value, ValueType};
 
 pub struct InterpretStatement<'a> {
     interpreter: &'a mut Interpreter
@@ -22,19 +24,27 @@ impl<'a> InterpretStatement<'a> {
 
     pub fn interpret_statement(&mut self, statement: Statement) -> io::Result<Return> {        
         match statement {
-            Statement::VariableDeclaration { name, public, data_type, value } => {                                
-                let value = if let Some(expression) = value {
+            Statement::VariableDeclaration { name, public, constant, data_type, value, doc: _ } => {                                
+                let mut value = if let Some(expression) = value {
                     self.interpreter.interpret_expression(expression)?
                 } else {
                     Value::new(None, ValueType::Null)
                 };
 
-                if let Some(data_type) = data_type {
+                // Type parameters are only checked by the analyzer, the value keeps its concrete type
+                if let Some(data_type) = data_type.filter(|data_type| !data_type.is_generic()) {
                     let value_data_type = value.get_type().get_data_type();
 
-                    if value_data_type != data_type && !DataType::is_void(&value_data_type) {
+                    if !data_type.accepts(&value_data_type) {
                         return Err(io::Error::new(io::ErrorKind::InvalidData, "Variable declaration type mismatch!"))
                     }
+
+                    value.get_type_mut().set_data_type(&data_type);
+                }
+
+                // A constant owns its storage, so no alias of another variable can change it
+                if constant {
+                    value = self.interpreter.copy_value(&value)?;
                 }
 
                 if public {
@@ -43,6 +53,35 @@ impl<'a> InterpretStatement<'a> {
                     self.interpreter.define_variable(name.as_str(), value)?;
                 }
 
+                if constant {
+                    self.interpreter.mark_constant(&name)?;
+                }
+
+                Ok(Return::Nothing)
+            },
+            Statement::PatternDeclaration { pattern, constant, data_type, value } => {
+                let mut value = self.interpreter.interpret_expression(value)?;
+
+                if let Some(data_type) = data_type.filter(|data_type| !data_type.is_generic()) {
+                    if !data_type.accepts(&value.get_type().get_data_type()) {
+                        return Err(io::Error::new(io::ErrorKind::InvalidData, "Variable declaration type mismatch!"))
+                    }
+
+                    value.get_type_mut().set_data_type(&data_type);
+                }
+
+                if constant {
+                    value = self.interpreter.copy_value(&value)?;
+                }
+
+                self.define_pattern(&pattern, value)?;
+
+                if constant {
+                    for name in pattern.names() {
+                        self.interpreter.mark_constant(name)?;
+                    }
+                }
+
                 Ok(Return::Nothing)
             },
             Statement::Assigment { name, value } => {
@@ -54,21 +93,18 @@ impl<'a> InterpretStatement<'a> {
             },
             Statement::AssigmentIndex { name, index, value } => {
                 let mut list_value = self.interpreter.get_variable(&name)?;
+                let owner = list_value.get_reference();
                 let list_value_type = list_value.get_type_mut();
                 let value_to_assign = self.interpreter.interpret_expression(value)?;
 
-                let mut indeces: Vec<i32> = Vec::new();
+                let mut indeces: Vec<ValueType> = Vec::new();
 
                 for i in index {
                     let value = self.interpreter.interpret_expression(i)?;
-                    let value_type = value.get_type().clone();
-                    
-                    if let ValueType::Integer(i32_index) = value_type {
-                        indeces.push(i32_index);
-                    }
+                    indeces.push(value.get_type().clone());
                 }
 
-                self.interpret_index_assigment(list_value_type, indeces, value_to_assign)?;
+                self.interpret_index_assigment(list_value_type, owner, indeces, value_to_assign)?;
                 self.interpreter.assign_variable(&name, list_value)?;
 
                 Ok(Return::Nothing)
@@ -147,34 +183,25 @@ impl<'a> InterpretStatement<'a> {
 
                 Ok(Return::Nothing)
             },
-            Statement::ForLoopStatement { name, list, body } => {
+            Statement::ForLoopStatement { pattern, list, body } => {
                 let list = self.interpreter.interpret_expression(list)?;
-                let list_type = list.get_type();
-                
-                self.interpret_for_loop(name, list_type, body)?;
-                
-                Ok(Return::Nothing)
+
+                self.interpret_for_loop(pattern, &list, body)
             },
             Statement::Expression { value } => {
                 self.interpreter.interpret_expression(value)?;
 
                 Ok(Return::Nothing)
             },
-            Statement::Function { name, public, return_type, parameters, body } => {
-                let mut capture = Environment::new();
-                {
-                    let local = self.interpreter.get_local();
-                    let local = local.borrow();
-
-                    capture = local.partially_clone();
-                }
-                
+            Statement::Function { name, public, return_type, parameters, body, doc: _ } => {
+                // Shared, not copied, so the function sees later updates of the variables around it
+                let capture = Capture::Strong(self.interpreter.get_local());
 
                 let value = Value::new(None, ValueType::Function { 
                     return_type, 
                     parameters, 
                     body, 
-                    capture: Rc::new(RefCell::new(capture))
+                    capture
                 });
                 
                 if public {
@@ -188,9 +215,11 @@ impl<'a> InterpretStatement<'a> {
             Statement::EarlyReturn { name, body } => {
                 let value = self.interpreter.get_variable(&name)?;
 
-                if DataType::is_void(&value.get_data_type()) {
+                if let ValueType::Null = value.get_type() {
                     let return_data = if let Some(body) = body {
+                        self.interpreter.enter_enviroment();
                         let return_result = self.interpret_block(body)?;
+                        self.interpreter.exit_enviroment()?;
 
                         match return_result {
                             Return::Success(_) => return_result,
@@ -206,6 +235,51 @@ impl<'a> InterpretStatement<'a> {
                 Ok(Return::Nothing)
             },
 
+            Statement::TryStatement { body, name, catch_body } => {
+                let local = self.interpreter.get_local();
+                let source_file = self.interpreter.source_file.clone();
+
+                self.interpreter.enter_enviroment();
+                let result = self.interpret_block(body);
+
+                let value = match result {
+                    Ok(value) => {
+                        self.interpreter.exit_enviroment()?;
+                        value
+                    },
+                    Err(error) => {
+                        self.interpreter.restore_enviroment(local);
+                        self.interpreter.source_file = source_file;
+
+                        self.interpreter.enter_enviroment();
+                        self.interpreter.define_variable(&name, Value::new(None, ValueType::String(error.to_string())))?;
+                        let value = self.interpret_block(catch_body)?;
+                        self.interpreter.exit_enviroment()?;
+
+                        value
+                    }
+                };
+
+                Ok(value)
+            },
+            Statement::Generator { body, data_type } => {
+                let local = self.interpreter.get_local();
+                let generator = Generator::new(body, local, self.interpreter.source_file.clone());
+
+                Ok(Return::Success(Value::new(None, ValueType::Generator { generator: Rc::new(RefCell::new(generator)), data_type })))
+            },
+            Statement::Yield { value: _ } => Err(io::Error::new(io::ErrorKind::InvalidData, "Yield outside of a generator!")),
+            Statement::Throw { value } => {
+                let value = self.interpreter.interpret_expression(value)?;
+
+                match value.get_type() {
+                    ValueType::String(message) => Err(io::Error::other(message.clone())),
+                    value_type => Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("Throw expects a string message, got {}!", value_type.get_data_type())
+                    ))
+                }
+            },
             Statement::Use { file_name, body } => {
                 let current_file = self.interpreter.source_file.clone();
 
@@ -215,35 +289,99 @@ impl<'a> InterpretStatement<'a> {
                 self.interpreter.source_file = current_file;
 
                 Ok(Return::Nothing)
-            }
+            },
+            Statement::Impl { data_type, interface, public, methods } => {
+                for method in methods {
+                    self.interpret_statement(method)?;
+                }
+
+                if let Some(interface) = interface {
+                    self.interpreter.add_implementation(interface, data_type, public);
+                }
+
+                Ok(Return::Nothing)
+            },
+            Statement::Interface { .. } => Ok(Return::Nothing)
         } 
     }
 
-    fn interpret_for_loop(&mut self, name: String, list_value: &ValueType, body: Vec<Statement>) -> io::Result<()> {
-        match list_value {
-            ValueType::String(str) => {
-                for char in str.chars() {
-                    self.interpreter.enter_enviroment();
+    fn interpret_for_loop(&mut self, pattern: Pattern, iterable: &Value, body: Vec<Statement>) -> io::Result<Return> {
+        let mut iteration = Iteration::from(iterable)?;
 
-                    self.interpreter.define_variable(name.as_str(), Value::new(None, ValueType::String(char.to_string())))?;
-                    
-                    self.interpret_block(body.clone())?;
-                    self.interpreter.exit_enviroment()?;
+        loop {
+            self.interpreter.enter_enviroment();
+
+            let Some(item) = iteration.next(self.interpreter)? else {
+                self.interpreter.exit_enviroment()?;
+                return Ok(Return::Nothing);
+            };
+
+            self.define_pattern(&pattern, item)?;
+
+            let return_value = self.interpret_block(body.clone())?;
+            self.interpreter.exit_enviroment()?;
+
+            if let Return::Success(return_value) = return_value {
+                return Ok(Return::Success(return_value));
+            }
+        }
+    }
+
+    pub fn define_pattern(&mut self, pattern: &Pattern, value: Value) -> io::Result<()> {
+        match pattern {
+            Pattern::Identifier(name) => {
+                match value.get_reference() {
+                    Some(reference) if !self.interpreter.same_scope(reference) => {
+                        self.interpreter.define_variable_by_reference(name.as_str(), reference)
+                    },
+                    _ => self.interpreter.define_variable(name.as_str(), value)
                 }
-                
-                Ok(())
             },
-            ValueType::List { references, data_type: _ } => {
-                for reference in references {
-                    self.interpreter.enter_enviroment();
-                    self.interpreter.define_variable_by_reference(name.as_str(), reference.clone())?;
-                    self.interpret_block(body.clone())?;
-                    self.interpreter.exit_enviroment()?;
+            Pattern::Tuple(patterns) => {
+                if let ValueType::Tuple { references, data_types: _ } = value.get_type() {
+                    if references.len() != patterns.len() {
+                        return Err(io::Error::new(
+                            io::ErrorKind::InvalidData,
+                            format!("Cannot destructure {} into {} names!", value.get_data_type(), patterns.len())
+                        ));
+                    }
+
+                    for (pattern, reference) in patterns.iter().zip(references) {
+                        let child = self.interpreter.get_variable_reference(*reference)?;
+                        self.define_pattern(pattern, child)?;
+                    }
+
+                    Ok(())
+                } else {
+                    Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("Cannot destructure {} into {} names!", value.get_data_type(), patterns.len())
+                    ))
                 }
+            },
+            Pattern::List(patterns) => {
+                if let ValueType::List { references, data_type: _ } = value.get_type() {
+                    if references.len() != patterns.len() {
+                        return Err(io::Error::new(
+                            io::ErrorKind::InvalidData,
+                            format!("Cannot destructure a list of {} elements into {} names!", references.len(), patterns.len())
+                        ));
+                    }
 
-                Ok(())
+                    for (pattern, reference) in patterns.iter().zip(references) {
+                        let child = self.interpreter.get_variable_reference(*reference)?;
+                        self.define_pattern(pattern, child)?;
+                    }
+
+                    Ok(())
+                } else {
+                    Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("Cannot destructure {} into {} names!", value.get_data_type(), patterns.len())
+                    ))
+                }
             },
-            _ => Err(io::Error::new(io::ErrorKind::InvalidData, "Unsupported type for loop!"))
+            Pattern::Wildcard => Ok(())
         }
     }
 
@@ -256,11 +394,14 @@ impl<'a> InterpretStatement<'a> {
         chars.into_iter().collect()
     }
 
-    fn interpret_index_assigment(&mut self, list_value: &mut ValueType, indeces: Vec<i32>, value_to_assign: Value) -> io::Result<()> {
+    fn interpret_index_assigment(&mut self, list_value: &mut ValueType, owner: Option<u64>, indeces: Vec<ValueType>, value_to_assign: Value) -> io::Result<()> {
         match list_value {
             ValueType::List { references: _, data_type: _ } => {
                 self.interpret_assign_list_index(list_value, indeces, value_to_assign)?;
             }
+            ValueType::Map { references: _, key_type: _, value_type: _ } => {
+                self.interpret_assign_map_index(list_value, owner, indeces, value_to_assign)?;
+            }
             ValueType::String(str) => {
                 self.interpret_assign_string_index(str, indeces, value_to_assign)?;
             }
@@ -270,21 +411,33 @@ impl<'a> InterpretStatement<'a> {
         Ok(())
     }
 
-    fn interpret_assign_string_index(&mut self, string: &mut String, indeces: Vec<i32>, value_to_assign: Value) -> io::Result<()> {
+    fn integer_index(&self, index: &ValueType, length: usize, collection: &str) -> io::Result<usize> {
+        if let ValueType::Integer(index) = index {
+            resolve_index(*index, length, collection)
+        } else {
+            Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Index in list or string requires integer type! Instead got {}.", index.get_data_type())
+            ))
+        }
+    }
+
+    fn interpret_assign_string_index(&mut self, string: &mut String, indeces: Vec<ValueType>, value_to_assign: Value) -> io::Result<()> {
         if indeces.len() != 1 {
             return Err(io::Error::new(io::ErrorKind::InvalidData, "String is not a matrix or something"))
         }
 
-        let index = indeces[0] as usize;
+        let index = self.integer_index(&indeces[0], string.chars().count(), "string")?;
 
         let value_type = value_to_assign.get_type();
 
         if let ValueType::String(string_to_change) = value_type {
-            if string_to_change.len() != 1 {
+            let chars: Vec<char> = string_to_change.chars().collect();
+
+            if chars.len() != 1 {
                 return Err(io::Error::new(io::ErrorKind::InvalidData, "You have to change with a char, not with a string!"));
             }
 
-            let chars: Vec<char> = string_to_change.chars().collect();
             let new_char: char = chars[0];
 
             *string = self.replace_char_at(string.clone(), index, new_char);
@@ -297,12 +450,9 @@ impl<'a> InterpretStatement<'a> {
         
     }
 
-    fn interpret_assign_list_index(&mut self, list_value: &mut ValueType, indeces: Vec<i32>, value_to_assign: Value) -> io::Result<()> {
-        if let ValueType::List { references, data_type: _ } = list_value {
-            let index = indeces[0] as usize;
-            if index >= references.len() {
-                return Err(io::Error::new(io::ErrorKind::InvalidData, "Out of index!"));
-            }
+    fn interpret_assign_list_index(&mut self, list_value: &mut ValueType, indeces: Vec<ValueType>, value_to_assign: Value) -> io::Result<()> {
+        if let ValueType::List { references, data_type } = list_value {
+            let index = self.integer_index(&indeces[0], references.len(), "list")?;
 
             let are_we_changing_child = indeces.len() == 1;
 
@@ -310,7 +460,9 @@ impl<'a> InterpretStatement<'a> {
             let mut child = self.interpreter.get_variable_reference(child_reference)?;
 
             if are_we_changing_child {
-                if child.get_type().get_data_type() != value_to_assign.get_type().get_data_type() {
+                let value_type = value_to_assign.get_data_type();
+
+                if child.get_data_type() != value_type && !data_type.accepts(&value_type) {
                     return Err(io::Error::new(io::ErrorKind::InvalidData, "Expected the same data type!"));
                 }
 
@@ -320,11 +472,12 @@ impl<'a> InterpretStatement<'a> {
                 return Ok(())
             }
 
-            let indeces: Vec<i32> = Vec::from(&indeces[1..]);
+            let indeces: Vec<ValueType> = Vec::from(&indeces[1..]);
             
             let value_type = child.get_type_mut();
 
-            self.interpret_index_assigment(value_type, indeces, value_to_assign)?;
+            self.interpret_index_assigment(value_type, Some(child_reference), indeces, value_to_assign)?;
+            self.interpreter.assign_variable_on_reference(child_reference, child)?;
         }
 
         
@@ -332,13 +485,59 @@ impl<'a> InterpretStatement<'a> {
         Ok(())
     }
 
+    fn interpret_assign_map_index(&mut self, map_value: &mut ValueType, owner: Option<u64>, indeces: Vec<ValueType>, value_to_assign: Value) -> io::Result<()> {
+        if let ValueType::Map { references, key_type, value_type } = map_value {
+            if indeces[0].get_data_type() != *key_type {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Map key type mismatch, expected {} got {}!", key_type, indeces[0].get_data_type())
+                ));
+            }
+
+            let key = MapKey::from_value_type(&indeces[0])?;
+
+            if indeces.len() == 1 {
+                if !value_type.accepts(&value_to_assign.get_data_type()) {
+                    return Err(io::Error::new(io::ErrorKind::InvalidData, "Expected the same data type!"));
+                }
+
+                match references.get(&key) {
+                    Some(reference) => self.interpreter.assign_variable_on_reference(*reference, value_to_assign)?,
+                    None => {
+                        let reference = match owner {
+                            Some(owner) => self.interpreter.create_value_beside(owner, value_to_assign)?,
+                            None => self.interpreter.create_value(value_to_assign)
+                        };
+
+                        references.insert(key, reference);
+                    }
+                }
+
+                return Ok(());
+            }
+
+            let child_reference = match references.get(&key) {
+                Some(reference) => *reference,
+                None => return Err(io::Error::new(io::ErrorKind::InvalidData, format!("Key {} not found in map!", key)))
+            };
+
+            let mut child = self.interpreter.get_variable_reference(child_reference)?;
+            let indeces: Vec<ValueType> = Vec::from(&indeces[1..]);
+
+            self.interpret_index_assigment(child.get_type_mut(), Some(child_reference), indeces, value_to_assign)?;
+            self.interpreter.assign_variable_on_reference(child_reference, child)?;
+        }
+
+        Ok(())
+    }
+
     fn interpret_add_equal(&mut self, name: &str, value: Value) -> io::Result<()> {
         let original_value = self.interpreter.get_variable(&name)?; 
         let reference = original_value.get_reference();
 
         match (original_value.get_type().clone(), value.get_type().clone()) {
             (ValueType::Integer(n1), ValueType::Integer(n2)) => {
-                let value = Value::new(reference, ValueType::Integer(n1 + n2));
+                let value = Value::new(reference, ValueType::Integer(checked_integer(n1.checked_add(n2), n1, "+", n2)?));
 
                 self.interpreter.assign_variable(name, value)?;
             },
@@ -375,7 +574,7 @@ impl<'a> InterpretStatement<'a> {
 
         match (original_value.get_type().clone(), value.get_type().clone()) {
             (ValueType::Integer(n1), ValueType::Integer(n2)) => {
-                let value = Value::new(reference, ValueType::Integer(n1 - n2));
+                let value = Value::new(reference, ValueType::Integer(checked_integer(n1.checked_sub(n2), n1, "-", n2)?));
 
                 self.interpreter.assign_variable(name, value)?;
             },
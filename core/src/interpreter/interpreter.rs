@@ -1,4 +1,5 @@
 use std::fmt::format;
+use std::collections::{BTreeMap, HashMap};
 use std::rc::Rc;
 use std::cell::RefCell;
 use std::io;
@@ -20,7 +21,9 @@ use super::value::{Value, ValueType};
 pub struct Interpreter {
     global: Rc<RefCell<Environment>>,
     local: Rc<RefCell<Environment>>,
-    pub source_file: String
+    pub source_file: String,
+    // Implementations that are running, inside its own `to_string` a value is displayed the default way
+    running_implementations: Vec<(String, DataType)>
 }
 
 impl Interpreter {
@@ -42,7 +45,7 @@ impl Interpreter {
                         let mut env = global.borrow_mut();
                         let _ = env.define_variable(name.clone(), Value::new(
                             None, 
-                            ValueType::RustFucntion { return_type: function.return_type.clone() }
+                            ValueType::RustFucntion { name: name.clone(), data_type: function.data_type() }
                         ));
                     }
                 }
@@ -52,7 +55,8 @@ impl Interpreter {
         Interpreter {
             global: global.clone(),
             local: local,
-            source_file: String::new()
+            source_file: String::new(),
+            running_implementations: Vec::new()
         }
     }
 
@@ -62,7 +66,8 @@ impl Interpreter {
         Interpreter {
             global: local.clone(),
             local: local,
-            source_file: String::new()
+            source_file: String::new(),
+            running_implementations: Vec::new()
         }
     }
 
@@ -84,6 +89,100 @@ impl Interpreter {
         local.create_value_without_name(value)
     }
 
+    pub fn create_value_beside(&mut self, owner: u64, value: Value) -> io::Result<u64> {
+        let mut children: Vec<Value> = Vec::new();
+        self.collect_children(&value, &mut children)?;
+
+        let mut local = self.local.borrow_mut();
+        Ok(local.create_value_beside(owner, value, children))
+    }
+
+    pub fn collect_children(&self, value: &Value, children: &mut Vec<Value>) -> io::Result<()> {
+        let references: Vec<u64> = match value.get_type() {
+            ValueType::List { references, data_type: _ } | ValueType::Tuple { references, data_types: _ } => references.clone(),
+            ValueType::Map { references, key_type: _, value_type: _ } => references.values().copied().collect(),
+            _ => Vec::new()
+        };
+
+        for reference in references {
+            let child = self.get_variable_reference(reference)?;
+            self.collect_children(&child, children)?;
+            children.push(child);
+        }
+
+        Ok(())
+    }
+
+    // A deep copy in new storage, writing to the copy never reaches the original
+    pub fn copy_value(&mut self, value: &Value) -> io::Result<Value> {
+        let value_type = match value.get_type() {
+            ValueType::List { references, data_type } => ValueType::List {
+                references: self.copy_references(references)?,
+                data_type: data_type.clone()
+            },
+            ValueType::Tuple { references, data_types } => ValueType::Tuple {
+                references: self.copy_references(references)?,
+                data_types: data_types.clone()
+            },
+            ValueType::Map { references, key_type, value_type } => {
+                let mut copies = BTreeMap::new();
+
+                for (key, reference) in references {
+                    let copy = self.copy_references(&[*reference])?;
+                    copies.insert(key.clone(), copy[0]);
+                }
+
+                ValueType::Map { references: copies, key_type: key_type.clone(), value_type: value_type.clone() }
+            },
+            value_type => value_type.clone()
+        };
+
+        Ok(Value::new(None, value_type))
+    }
+
+    fn copy_references(&mut self, references: &[u64]) -> io::Result<Vec<u64>> {
+        let mut copies: Vec<u64> = Vec::new();
+
+        for reference in references {
+            let child = self.get_variable_reference(*reference)?;
+            let copy = self.copy_value(&child)?;
+
+            copies.push(self.create_value(copy));
+        }
+
+        Ok(copies)
+    }
+
+    // The variable and everything inside it reject writes, whichever name they come through
+    pub fn mark_constant(&mut self, name: &str) -> io::Result<()> {
+        let value = self.get_variable(name)?;
+
+        let mut children: Vec<Value> = Vec::new();
+        self.collect_children(&value, &mut children)?;
+
+        let mut local = self.local.borrow_mut();
+
+        for reference in value.get_reference().into_iter().chain(children.iter().filter_map(Value::get_reference)) {
+            local.mark_constant(reference, name);
+        }
+
+        Ok(())
+    }
+
+    // Returns a reference to keep inside a collection, existing variables are shared instead of copied
+    pub fn child_reference(&mut self, value: Value) -> u64 {
+        match value.get_reference() {
+            Some(reference) if self.variable_exists(reference) => {
+                if !self.same_scope(reference) {
+                    self.create_reference(reference);
+                }
+
+                reference
+            },
+            _ => self.create_value(value)
+        }
+    }
+
     pub fn get_variable(&self, name: &str) -> io::Result<Value> {
         let local = self.local.borrow();
         local.get_variable(name)
@@ -160,6 +259,18 @@ impl Interpreter {
         self.local = new_local;
     }
 
+    // Jumps back to a scope that is still on the stack, used when an error unwinds past `exit_enviroment`
+    pub fn restore_enviroment(&mut self, env: Rc<RefCell<Environment>>) {
+        let native = NativeRegistry::get();
+        {
+            let mut native = native.borrow_mut();
+
+            native.local = Some(env.clone());
+        }
+
+        self.local = env;
+    }
+
     pub fn exit_enviroment(&mut self) -> io::Result<()> {
         let new_env = {
             let local = self.local.clone();
@@ -180,7 +291,9 @@ impl Interpreter {
                 native.local = Some(env.clone());
             }
             
-            self.local = env;
+            let left = std::mem::replace(&mut self.local, env);
+            Environment::release(left);
+
             Ok(())
         } else {
             Err(io::Error::new(io::ErrorKind::InvalidData, "No parent enviroment!"))
@@ -217,9 +330,106 @@ impl Interpreter {
         local.move_to_parent(value);
     }
 
-    fn append_environment(&mut self, env: Rc<RefCell<Environment>>) {
+    fn set_closure(&mut self, env: Rc<RefCell<Environment>>) {
         let mut local = self.local.borrow_mut();
-        local.append_environment(env.clone());
+        local.set_closure(env);
+    }
+
+    pub fn add_implementation(&mut self, interface: String, data_type: DataType, public: bool) {
+        let env = if public { &self.global } else { &self.local };
+
+        env.borrow_mut().add_implementation(interface, data_type);
+    }
+
+    // Runs a method of a built-in interface if the type of the first argument implements it,
+    // `None` means the default behavior applies
+    pub fn call_builtin_interface(&mut self, interface: &str, method: &str, args: Vec<Value>) -> io::Result<Option<Value>> {
+        let data_type = args[0].get_data_type();
+        let implementation = (interface.to_string(), data_type.clone());
+
+        if !self.local.borrow().implements(interface, &data_type) || self.running_implementations.contains(&implementation) {
+            return Ok(None);
+        }
+
+        let method = data_type.method_name(method);
+        let function = self.get_variable(&method)?;
+
+        self.running_implementations.push(implementation);
+        let result = self.call_value(&method, &function, args);
+        self.running_implementations.pop();
+
+        result.map(Some)
+    }
+
+    // Whether two values of the same type are equal, a type implementing `Equal` is compared through it,
+    // also inside collections, which are compared by their items
+    pub fn values_equal(&mut self, left: &Value, right: &Value) -> io::Result<bool> {
+        if let Some(equal) = self.call_builtin_interface("Equal", "equals", vec![left.clone(), right.clone()])?
+            && let ValueType::Boolean(equal) = equal.get_type() {
+            return Ok(*equal);
+        }
+
+        match (left.get_type(), right.get_type()) {
+            (ValueType::List { references, .. }, ValueType::List { references: others, .. })
+            | (ValueType::Tuple { references, .. }, ValueType::Tuple { references: others, .. }) => {
+                if references.len() != others.len() {
+                    return Ok(false);
+                }
+
+                for (reference, other) in references.iter().zip(others) {
+                    if !self.references_equal(*reference, *other)? {
+                        return Ok(false);
+                    }
+                }
+
+                Ok(true)
+            },
+            (ValueType::Map { references, .. }, ValueType::Map { references: others, .. }) => {
+                if references.len() != others.len() {
+                    return Ok(false);
+                }
+
+                for (key, reference) in references {
+                    match others.get(key) {
+                        Some(other) if self.references_equal(*reference, *other)? => {},
+                        _ => return Ok(false)
+                    }
+                }
+
+                Ok(true)
+            },
+            (left, right) => Ok(left == right)
+        }
+    }
+
+    fn references_equal(&mut self, reference: u64, other: u64) -> io::Result<bool> {
+        let left = self.get_variable_reference(reference)?;
+        let right = self.get_variable_reference(other)?;
+
+        self.values_equal(&left, &right)
+    }
+
+    pub fn display_value(&mut self, value: Value) -> io::Result<Value> {
+        Ok(Value::new(None, ValueType::String(self.value_to_string(&value)?)))
+    }
+
+    // The printed form of a value, a type implementing `Display` is shown through it, also inside collections
+    pub fn value_to_string(&mut self, value: &Value) -> io::Result<String> {
+        let local = self.get_local();
+
+        Environment::format_value(value, &|reference| local.borrow().get_by_reference(reference), &mut |value| {
+            match self.call_builtin_interface("Display", "to_string", vec![value.clone()])? {
+                Some(displayed) => Ok(Some(local.borrow().value_to_string(&displayed)?)),
+                None => Ok(None)
+            }
+        })
+    }
+
+    fn displays_arguments(&self, name: &str) -> bool {
+        let registry = NativeRegistry::get();
+        let registry = registry.borrow();
+
+        matches!(registry.get_native(name), Some(NativeTypes::NativeFunction(native_function)) if native_function.display_arguments)
     }
 
     pub fn call_native_function(&self, name: &str, args: Vec<Value>) -> io::Result<Value> {
@@ -249,10 +459,13 @@ impl Interpreter {
 
         self.enter_enviroment();
 
-        self.append_environment(capture.clone());
+        self.set_closure(capture.clone());
+
+        let type_parameters = DataType::Tuple(DataType::from_parameters(parameters)).generics();
+        let mut bindings: HashMap<String, DataType> = HashMap::new();
 
         for (arg, parameter) in args.iter().zip(parameters) {
-            if arg.get_type().get_data_type() != parameter.data_type && !DataType::is_void(&arg.get_data_type()) {
+            if parameter.data_type.unify(&arg.get_data_type(), &type_parameters, &mut bindings).is_err() {
                 return Err(io::Error::new(io::ErrorKind::InvalidData, format!("Missmatch in function's singature \"{}\"!", name)));
             }
 
@@ -273,6 +486,13 @@ impl Interpreter {
                         self.move_to_parent(list_value);
                     }
                 }
+
+                if let ValueType::Map { references, key_type: _, value_type: _ } = value.get_type() {
+                    for reference in references.values() {
+                        let map_value = self.get_variable_reference(*reference)?;
+                        self.move_to_parent(map_value);
+                    }
+                }
                 
                 if let Some(reference) = value.get_reference() {
                     if self.same_scope(reference) {
@@ -282,7 +502,18 @@ impl Interpreter {
                 
                 self.exit_enviroment()?;
 
-                if value.get_data_type() != *return_type {
+                // A captured variable lives in the closure, which the caller cannot see
+                if let Some(reference) = value.get_reference()
+                    && !self.variable_exists(reference) {
+                    value.clear_reference();
+                }
+
+                let return_type = return_type.substitute(&bindings);
+
+                // `return;` and `a?;` end a void function with null
+                let empty_return = DataType::is_void(&return_type) && matches!(value.get_type(), ValueType::Null);
+
+                if !return_type.is_generic() && !empty_return && !return_type.accepts(&value.get_data_type()) {
                     return Err(io::Error::new(
                         io::ErrorKind::InvalidData, 
                         format!("Different return types in {} ({} != {})", name, return_type, value.get_data_type())
@@ -301,16 +532,38 @@ impl Interpreter {
     pub fn call_function(&mut self, name: &str, args: Vec<Value>) -> io::Result<Value> {        
         let value = self.get_variable(name)?;
 
+        self.call_value(name, &value, args)
+    }
+
+    // `name` is only used in error messages, natives are looked up by the name they were registered with
+    pub fn call_value(&mut self, name: &str, value: &Value, args: Vec<Value>) -> io::Result<Value> {
         match value.get_type() {
             ValueType::Function { return_type, parameters, body, capture } => {
-                self.call_internal_function(name, args, parameters, body, capture.clone(), return_type)
+                self.call_internal_function(name, args, parameters, body, capture.get()?, return_type)
             },
-            ValueType::RustFucntion { return_type: _ } => {
+            ValueType::RustFucntion { name, data_type: _ } => {
+                let args = if self.displays_arguments(name) {
+                    args.into_iter().map(|arg| self.display_value(arg)).collect::<io::Result<Vec<Value>>>()?
+                } else {
+                    args
+                };
+
                 self.call_native_function(name, args)
             },
+            ValueType::Generator { generator, data_type: _ } => {
+                if !args.is_empty() {
+                    return Err(io::Error::new(io::ErrorKind::InvalidData, format!("Generator {} takes no arguments!", name)));
+                }
+
+                let Ok(mut generator) = generator.try_borrow_mut() else {
+                    return Err(io::Error::new(io::ErrorKind::InvalidData, format!("Generator {} is already running!", name)));
+                };
+
+                let value = generator.resume(self)?;
+
+                Ok(value.unwrap_or(Value::new(None, ValueType::Null)))
+            },
             _ => Err(io::Error::new(io::ErrorKind::InvalidData, format!("Variable {} is not a function!", name)))
         }
-
-        
     }
 }
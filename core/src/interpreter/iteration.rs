@@ -0,0 +1,83 @@
+use std::collections::btree_map;
+use std::collections::btree_set;
+use std::io;
+use std::vec;
+
+use crate::parser::data_type::DataType;
+
+use super::interpreter::Interpreter;
+use super::range::Range;
+use super::value::{MapKey, Value, ValueType};
+
+
+// The state of a `for` loop, elements are produced one at a time so nothing is copied up front
+pub enum Iteration {
+    Characters(vec::IntoIter<char>),
+    References(vec::IntoIter<u64>),
+    Entries {
+        entries: btree_map::IntoIter<MapKey, u64>,
+        data_types: DataType
+    },
+    Keys(btree_set::IntoIter<MapKey>),
+    Range {
+        range: Range,
+        index: i64
+    },
+    // A user-defined iterator is a function that returns the next element, or null once it is done
+    Function(Value)
+}
+
+impl Iteration {
+    pub fn from(value: &Value) -> io::Result<Iteration> {
+        match value.get_type() {
+            ValueType::String(string) => Ok(Iteration::Characters(string.chars().collect::<Vec<char>>().into_iter())),
+            ValueType::List { references, data_type: _ } => Ok(Iteration::References(references.clone().into_iter())),
+            ValueType::Map { references, key_type, value_type } => Ok(Iteration::Entries {
+                entries: references.clone().into_iter(),
+                data_types: DataType::Tuple(vec![key_type.clone(), value_type.clone()])
+            }),
+            ValueType::Set { values, data_type: _ } => Ok(Iteration::Keys(values.clone().into_iter())),
+            ValueType::Range(range) => Ok(Iteration::Range { range: range.clone(), index: 0 }),
+            ValueType::Function { .. } | ValueType::Generator { .. } => Ok(Iteration::Function(value.clone())),
+            _ => Err(io::Error::new(io::ErrorKind::InvalidData, "Unsupported type for loop!"))
+        }
+    }
+
+    pub fn next(&mut self, interpreter: &mut Interpreter) -> io::Result<Option<Value>> {
+        match self {
+            Iteration::Characters(characters) => {
+                Ok(characters.next().map(|char| Value::new(None, ValueType::String(char.to_string()))))
+            },
+            Iteration::References(references) => match references.next() {
+                Some(reference) => Ok(Some(interpreter.get_variable_reference(reference)?)),
+                None => Ok(None)
+            },
+            Iteration::Entries { entries, data_types } => match entries.next() {
+                Some((key, reference)) => {
+                    let key_reference = interpreter.create_value(Value::new(None, key.to_value_type()));
+
+                    Ok(Some(Value::new(None, ValueType::Tuple {
+                        references: vec![key_reference, reference],
+                        data_types: data_types.clone()
+                    })))
+                },
+                None => Ok(None)
+            },
+            Iteration::Keys(keys) => Ok(keys.next().map(|key| Value::new(None, key.to_value_type()))),
+            Iteration::Range { range, index } => {
+                let number = range.get(*index);
+                *index += 1;
+
+                Ok(number.map(|number| Value::new(None, ValueType::Integer(number))))
+            },
+            Iteration::Function(function) => {
+                let item = interpreter.call_value("iterator", function, Vec::new())?;
+
+                match item.get_type() {
+                    ValueType::Null => Ok(None),
+                    _ => Ok(Some(item))
+                }
+            }
+        }
+    }
+}
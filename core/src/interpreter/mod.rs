@@ -1,8 +1,12 @@
 pub mod interpreter;
 pub mod value;
 pub mod enviroment;
+pub mod range;
+pub mod generator;
+pub mod capture;
 
 mod interpret_expression;
 mod interpret_statement;
+mod iteration;
 mod return_value;
 mod variable_slot;
\ No newline at end of file
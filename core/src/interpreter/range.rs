@@ -0,0 +1,76 @@
+use std::fmt::Display;
+use std::io;
+
+
+// `start..end step n`, the numbers are produced one at a time and never stored
+#[derive(Debug, Clone, PartialEq)]
+pub struct Range {
+    pub start: i64,
+    pub end: i64,
+    pub step: i64,
+    pub inclusive: bool
+}
+
+impl Range {
+    pub fn new(start: i64, end: i64, step: i64, inclusive: bool) -> io::Result<Range> {
+        if step == 0 {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "Range step cannot be zero!"));
+        }
+
+        Ok(Range { start, end, step, inclusive })
+    }
+
+    // A negative step walks down, so `10..0 step -2` is 10, 8, 6, 4, 2
+    pub fn contains(&self, number: i64) -> bool {
+        let inside = match (self.step > 0, self.inclusive) {
+            (true, true) => self.start <= number && number <= self.end,
+            (true, false) => self.start <= number && number < self.end,
+            (false, true) => self.end <= number && number <= self.start,
+            (false, false) => self.end < number && number <= self.start
+        };
+
+        // Far apart bounds do not fit into an int, their distance always fits into an i128
+        inside && (number as i128 - self.start as i128) % self.step as i128 == 0
+    }
+
+    pub fn get(&self, index: i64) -> Option<i64> {
+        let number = self.start.checked_add(self.step.checked_mul(index)?)?;
+
+        (index >= 0 && self.contains(number)).then_some(number)
+    }
+
+    pub fn len(&self) -> io::Result<i64> {
+        i64::try_from(self.count()).map_err(|_| io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Integer overflow: len({}) does not fit into int!", self)
+        ))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.count() == 0
+    }
+
+    fn count(&self) -> i128 {
+        let distance = (self.end as i128 - self.start as i128) * self.step.signum() as i128;
+        let distance = if self.inclusive { distance + 1 } else { distance };
+
+        if distance <= 0 {
+            return 0;
+        }
+
+        let step = self.step.unsigned_abs() as i128;
+
+        (distance + step - 1) / step
+    }
+}
+
+impl Display for Range {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let operator = if self.inclusive { "..=" } else { ".." };
+
+        match self.step {
+            1 => write!(f, "{}{}{}", self.start, operator, self.end),
+            step => write!(f, "{}{}{} step {}", self.start, operator, self.end, step)
+        }
+    }
+}
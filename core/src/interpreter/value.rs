@@ -1,14 +1,26 @@
 use std::cell::RefCell;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Display;
+use std::io;
 use std::rc::Rc;
 
-use crate::interpreter::enviroment::Environment;
+use crate::interpreter::capture::Capture;
+use crate::interpreter::generator::Generator;
+use crate::interpreter::range::Range;
 use crate::parser::data_type::DataType;
 use crate::parser::parameter::Parameter;
 use crate::parser::statement::Statement;
 
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum MapKey {
+    Integer(i64),
+    String(String),
+    Boolean(bool)
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum ValueType {
-    Integer(i32),
+    Integer(i64),
     Float(f64),
     String(String),
     Boolean(bool),
@@ -21,14 +33,30 @@ pub enum ValueType {
         references: Vec<u64>,
         data_types: DataType
     },
+    Map {
+        references: BTreeMap<MapKey, u64>,
+        key_type: DataType,
+        value_type: DataType
+    },
+    Set {
+        values: BTreeSet<MapKey>,
+        data_type: DataType
+    },
+    Range(Range),
     Function {
         return_type: DataType,
         parameters: Vec<Parameter>,
         body: Vec<Statement>,
-        capture: Rc<RefCell<Environment>>
+        capture: Capture
     },
     RustFucntion {
-        return_type: DataType
+        name: String,
+        data_type: DataType
+    },
+    // Calling it resumes the generator, the type is the iterator function it was declared as
+    Generator {
+        generator: Rc<RefCell<Generator>>,
+        data_type: DataType
     }
 }
 
@@ -86,12 +114,72 @@ impl ValueType {
                     parameter_types.push(parameter.data_type.clone());
                 }
 
-                DataType::Function { parameters: parameter_types, return_type: Box::new(return_type.clone()) }
+                // Values only keep their declared signature, every type parameter in it is free again
+                let type_parameters = DataType::Tuple(parameter_types.clone()).generics();
+
+                DataType::Function {
+                    type_parameters,
+                    parameters: parameter_types,
+                    mutable_parameters: DataType::mutable_parameters(parameters),
+                    return_type: Box::new(return_type.clone())
+                }
             },
-            ValueType::RustFucntion { return_type } => DataType::RustFunction { return_type: Box::new(return_type.clone()) },
+            ValueType::RustFucntion { name: _, data_type } => data_type.clone(),
             ValueType::List { references: _, data_type } => DataType::List(Box::new(data_type.clone())),
-            ValueType::Null => DataType::void(),
-            ValueType::Tuple { references: _, data_types } => data_types.clone()
+            ValueType::Null => DataType::Null,
+            ValueType::Tuple { references: _, data_types } => data_types.clone(),
+            ValueType::Map { references: _, key_type, value_type } => {
+                DataType::Map(Box::new(key_type.clone()), Box::new(value_type.clone()))
+            },
+            ValueType::Set { values: _, data_type } => DataType::Set(Box::new(data_type.clone())),
+            ValueType::Range(_) => DataType::Range,
+            ValueType::Generator { generator: _, data_type } => data_type.clone()
+        }
+    }
+
+    // Collections take the declared element type, so `let xs: [int?] = [1];` can later hold null
+    pub fn set_data_type(&mut self, declared: &DataType) {
+        match (self, declared) {
+            (ValueType::List { references: _, data_type }, DataType::List(declared))
+            | (ValueType::Set { values: _, data_type }, DataType::Set(declared)) => {
+                *data_type = *declared.clone();
+            },
+            (ValueType::Map { references: _, key_type: _, value_type }, DataType::Map(_, declared)) => {
+                *value_type = *declared.clone();
+            },
+            _ => {}
+        }
+    }
+}
+
+impl MapKey {
+    pub fn from_value_type(value_type: &ValueType) -> io::Result<MapKey> {
+        match value_type {
+            ValueType::Integer(number) => Ok(MapKey::Integer(*number)),
+            ValueType::String(string) => Ok(MapKey::String(string.clone())),
+            ValueType::Boolean(boolean) => Ok(MapKey::Boolean(*boolean)),
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Map key has to be int, string or boolean, got {}!", value_type.get_data_type())
+            ))
+        }
+    }
+
+    pub fn to_value_type(&self) -> ValueType {
+        match self {
+            MapKey::Integer(number) => ValueType::Integer(*number),
+            MapKey::String(string) => ValueType::String(string.clone()),
+            MapKey::Boolean(boolean) => ValueType::Boolean(*boolean)
+        }
+    }
+}
+
+impl Display for MapKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MapKey::Integer(number) => write!(f, "{}", number),
+            MapKey::String(string) => write!(f, "\"{}\"", string),
+            MapKey::Boolean(boolean) => write!(f, "{}", boolean)
         }
     }
 }
\ No newline at end of file
@@ -11,6 +11,7 @@ use crate::interpreter::interpreter::Interpreter;
 pub struct KyrylScript {
     global: Rc<RefCell<Environment>>,
     interpreter: Rc<RefCell<Interpreter>>,
+    warnings: Vec<String>
 }
 
 impl KyrylScript {
@@ -20,7 +21,8 @@ impl KyrylScript {
         
         KyrylScript {
             global,
-            interpreter
+            interpreter,
+            warnings: Vec::new()
         }
     }
 
@@ -29,11 +31,19 @@ impl KyrylScript {
         
         KyrylScript {
             global: global.clone(),
-            interpreter
+            interpreter,
+            warnings: Vec::new()
         }
     }
 
+    // Diagnostics of the last parsed script that did not stop it, the embedder decides how to show them
+    pub fn get_warnings(&self) -> &Vec<String> {
+        &self.warnings
+    }
+
     pub fn run_from_file(&mut self, path: &str) -> io::Result<()> {
+        self.warnings.clear();
+
         let mut lexer = Lexer::load(path)?;
         lexer.lexer()?;
 
@@ -42,6 +52,7 @@ impl KyrylScript {
 
         let mut parser = Parser::new(tokens, token_pos);
         let block = parser.start();
+        self.warnings = parser.get_warnings().clone();
 
         if let Err(e) = block {
             return Err(io::Error::new(
@@ -51,6 +62,7 @@ impl KyrylScript {
         }
 
         let block = block?;
+
         let mut interpreter = self.interpreter.borrow_mut();
 
         let interpreter_result = interpreter.interpret_statements(block);
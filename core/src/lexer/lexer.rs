@@ -1,48 +1,53 @@
 use std::fs::read_to_string;
 use std::io;
+use std::num::IntErrorKind;
 
-use super::token::Token;
-use super::token::COMMENT;
-use super::token::{get_token, is_symbol};
+use super::token::{FormatToken, Token};
+use super::token::{BLOCK_COMMENT_END, BLOCK_COMMENT_START, COMMENT, DOC_COMMENT};
+use super::token::{get_token, is_symbol, MAX_SYMBOL_LENGTH};
 
-use super::lexer_state::LexerState;
 use super::token_pos::TokenPos;
 
 
 pub struct Lexer {
     tokens: Vec<Token>,
     token_pos: Vec<TokenPos>,
-    source_lines: Vec<String>,
+    source: Vec<char>,
     source_path: Option<String>,
-    current_line_pos: i32
+    current_line_pos: i32,
+    current_column_pos: i32,
+    token_column_pos: i32,
+    cur: usize
 }
 
 impl Lexer {
     pub fn new(source: String) -> Lexer {
-        let source_lines: Vec<String> = source.lines().map(|s| s.to_string()).collect();
-
         Lexer {
             tokens: Vec::new(),
             token_pos: Vec::new(),
-            source_lines: source_lines,
+            source: source.chars().collect(),
             source_path: None,
-            current_line_pos: 0
+            current_line_pos: 0,
+            current_column_pos: 0,
+            token_column_pos: 0,
+            cur: 0
         }
     }
-    
+
     pub fn load(source_path: &str) -> io::Result<Lexer> {
         let result = read_to_string(source_path);
 
         match result {
             Ok(source) => {
-                let source_lines: Vec<String> = source.lines().map(|s| s.to_string()).collect();
-
                 Ok(Lexer {
                     tokens: Vec::new(),
                     token_pos: Vec::new(),
-                    source_lines: source_lines,
+                    source: source.chars().collect(),
                     source_path: Some(source_path.to_string()),
-                    current_line_pos: 0
+                    current_line_pos: 0,
+                    current_column_pos: 0,
+                    token_column_pos: 0,
+                    cur: 0
                 })
             }
             Err(_) => Err(io::Error::new(
@@ -60,161 +65,532 @@ impl Lexer {
         &self.token_pos
     }
 
+    fn error(&self, message: String) -> io::Error {
+        let file = match &self.source_path {
+            Some(path) => path.as_str(),
+            None => "Main"
+        };
+
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("kyryl-script: At {}:{}:{}: {}", file, self.current_line_pos + 1, self.current_column_pos + 1, message)
+        )
+    }
+
     fn add_token(&mut self, token: Token) {
+        self.add_token_at(token, self.current_line_pos);
+    }
+
+    fn add_token_at(&mut self, token: Token, line: i32) {
         self.tokens.push(token);
 
-        let token_pos = TokenPos::from(self.source_path.clone(), self.current_line_pos);
+        let token_pos = TokenPos::from(self.source_path.clone(), line, self.token_column_pos);
         self.token_pos.push(token_pos);
     }
 
-    fn add_token_text(&mut self, buffer: &str) {
-        if let Some(keyword) = get_token(buffer) {
-            self.add_token(keyword);
+    fn peek(&self) -> Option<char> {
+        self.source.get(self.cur).copied()
+    }
+
+    fn peek_next(&self) -> Option<char> {
+        self.source.get(self.cur + 1).copied()
+    }
+
+    // Every character goes through here, so line and column tracking stays in one place
+    fn advance(&mut self) -> Option<char> {
+        let current_char = self.peek()?;
+        self.cur += 1;
+
+        if current_char == '\n' {
+            self.current_line_pos += 1;
+            self.current_column_pos = 0;
         } else {
-            self.add_token(Token::Identifier(buffer.to_string()));
+            self.current_column_pos += 1;
         }
+
+        Some(current_char)
     }
 
-    pub fn lex_line(&mut self, mut line: String) -> io::Result<()> { 
-        line.push(' ');
-        let mut cur: usize = 0;
-        let mut state = LexerState::None;
+    fn advance_by(&mut self, count: usize) {
+        for _ in 0..count {
+            self.advance();
+        }
+    }
 
-        let mut buffer = String::new();
+    fn match_char(&mut self, expected: char) -> bool {
+        if self.peek() == Some(expected) {
+            self.advance();
+            true
+        } else {
+            false
+        }
+    }
 
-        while cur < line.len() {
-            let current_char = line.chars().nth(cur).unwrap();
-
-            match state {
-                LexerState::None => {
-                    if current_char.is_whitespace() {
-                        if buffer.len() > 0 {
-                            self.add_token_text(&buffer);
-                            buffer.clear();
-                        }
-                    } else if current_char.is_alphabetic() {
-                        state = LexerState::Identifier;
-                        buffer.push(current_char);
-                    } else if current_char.is_numeric() {
-                        state = LexerState::Number;
-                        buffer.push(current_char);
-                    } else if current_char == '"' {
-                        state = LexerState::String;
-                    } else if is_symbol(current_char) {
-                        state = LexerState::Symbol;
-                        buffer.push(current_char);
-                    }
-                }
+    fn starts_with(&self, text: &str) -> bool {
+        text.chars().enumerate().all(|(i, c)| self.source.get(self.cur + i) == Some(&c))
+    }
 
-                LexerState::String => {
-                    if current_char == '"' {
-                        self.add_token(Token::StringLiteral(buffer.clone()));
-                        buffer.clear();
-                        state = LexerState::None;
-                    } else {
-                        buffer.push(current_char);
-                    }
-                }
+    fn text_from(&self, start: usize) -> String {
+        self.source[start..self.cur].iter().collect()
+    }
 
-                LexerState::Number => {
-                    if current_char.is_numeric() || current_char == '.' {
-                        buffer.push(current_char);
-                    } else if current_char == 'f' {
-                        if let Ok(num) = buffer.parse::<f64>() {
-                            self.add_token(Token::FloatLiteral(num));
-                            buffer.clear();
-                            state = LexerState::None;
-                        } else {
-                            return Err(io::Error::new(io::ErrorKind::InvalidData, "Invalid float literal"));
-                        }
-                    } else {
-                        if let Ok(num) = buffer.parse::<i32>() {
-                            self.add_token(Token::IntegerLiteral(num));
-                            buffer.clear();
-                            state = LexerState::None;
-
-                            continue;
-                        } else {
-                            return Err(io::Error::new(io::ErrorKind::InvalidData, "Invalid integer literal"));
-                        }
-                    }
+    fn is_identifier_char(c: char) -> bool {
+        c.is_alphanumeric() || c == '_'
+    }
+
+    pub fn lexer(&mut self) -> io::Result<()> {
+        while let Some(current_char) = self.peek() {
+            self.token_column_pos = self.current_column_pos;
+
+            if current_char.is_whitespace() {
+                self.advance();
+            } else if self.starts_with(DOC_COMMENT) && !self.starts_with("////") {
+                self.lex_doc_comment();
+            } else if self.starts_with(COMMENT) {
+                self.skip_line();
+            } else if self.starts_with(BLOCK_COMMENT_START) {
+                self.skip_block_comment()?;
+            } else if current_char == 'r' && matches!(self.peek_next(), Some('"') | Some('#')) {
+                self.advance();
+                self.lex_raw_string()?;
+            } else if current_char == 'f' && self.peek_next() == Some('"') {
+                self.advance_by(2);
+                self.lex_format_string()?;
+            } else if current_char.is_alphabetic() || current_char == '_' {
+                self.lex_identifier();
+            } else if current_char.is_ascii_digit() {
+                self.lex_number()?;
+            } else if self.starts_with("\"\"\"") {
+                self.advance_by(3);
+                self.lex_string(true)?;
+            } else if current_char == '"' {
+                self.advance();
+                self.lex_string(false)?;
+            } else if is_symbol(current_char) {
+                self.lex_symbol()?;
+            } else {
+                return Err(self.error(format!("Unexpected character '{}'!", current_char)));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn lex_identifier(&mut self) {
+        let start = self.cur;
+
+        while self.peek().is_some_and(Lexer::is_identifier_char) {
+            self.advance();
+        }
+
+        let text = self.text_from(start);
+
+        match get_token(&text) {
+            Some(keyword) => self.add_token(keyword),
+            None => self.add_token(Token::Identifier(text))
+        }
+    }
+
+    fn lex_digits(&mut self, radix: u32) -> String {
+        let mut digits = String::new();
+
+        while let Some(c) = self.peek() {
+            if c.is_digit(radix) {
+                digits.push(c);
+            } else if c != '_' {
+                break;
+            }
+
+            self.advance();
+        }
+
+        digits
+    }
+
+    fn lex_number(&mut self) -> io::Result<()> {
+        let start = self.cur;
+
+        let radix = match (self.peek(), self.peek_next()) {
+            (Some('0'), Some('x' | 'X')) => 16,
+            (Some('0'), Some('b' | 'B')) => 2,
+            (Some('0'), Some('o' | 'O')) => 8,
+            _ => 10
+        };
+
+        if radix != 10 {
+            self.advance_by(2);
+            let digits = self.lex_digits(radix);
+
+            self.check_number_end(start)?;
+
+            if digits.is_empty() {
+                return Err(self.error(format!("Invalid integer literal {}!", self.text_from(start))));
+            }
+
+            let number = self.parse_integer(&digits, radix, start)?;
+            self.add_token(Token::IntegerLiteral(number));
+
+            return Ok(());
+        }
+
+        let mut number = self.lex_digits(10);
+        let mut is_float = false;
+
+        // Tuple indexing like `a.0.1` must not be read as a float
+        let after_dot = matches!(self.tokens.last(), Some(Token::Dot | Token::QuestionDot));
+
+        if !after_dot && self.peek() == Some('.') && self.peek_next().is_some_and(|c| c.is_ascii_digit()) {
+            self.advance();
+            number.push('.');
+            number.push_str(&self.lex_digits(10));
+            is_float = true;
+        }
+
+        if !after_dot && matches!(self.peek(), Some('e' | 'E')) {
+            self.advance();
+            number.push('e');
+
+            if let Some(sign) = self.peek().filter(|c| *c == '+' || *c == '-') {
+                number.push(sign);
+                self.advance();
+            }
+
+            let digits = self.lex_digits(10);
+
+            if digits.is_empty() {
+                return Err(self.error(format!("Invalid float literal {}, exponent has no digits!", self.text_from(start))));
+            }
+
+            number.push_str(&digits);
+            is_float = true;
+        }
+
+        if self.peek() == Some('f') && !self.peek_next().is_some_and(Lexer::is_identifier_char) {
+            self.advance();
+            is_float = true;
+        }
+
+        self.check_number_end(start)?;
+
+        if is_float {
+            match number.parse::<f64>() {
+                Ok(number) => self.add_token(Token::FloatLiteral(number)),
+                Err(_) => return Err(self.error(format!("Invalid float literal {}!", self.text_from(start))))
+            }
+        } else {
+            let number = self.parse_integer(&number, 10, start)?;
+            self.add_token(Token::IntegerLiteral(number));
+        }
+
+        Ok(())
+    }
+
+    // A number directly followed by letters (`12abc`) is a typo rather than two tokens
+    fn check_number_end(&mut self, start: usize) -> io::Result<()> {
+        if self.peek().is_some_and(Lexer::is_identifier_char) {
+            while self.peek().is_some_and(Lexer::is_identifier_char) {
+                self.advance();
+            }
+
+            return Err(self.error(format!("Invalid number literal {}!", self.text_from(start))));
+        }
+
+        Ok(())
+    }
+
+    fn parse_integer(&self, digits: &str, radix: u32, start: usize) -> io::Result<i64> {
+        match i64::from_str_radix(digits, radix) {
+            Ok(number) => Ok(number),
+            Err(e) if matches!(e.kind(), IntErrorKind::PosOverflow) => {
+                Err(self.error(format!("Integer literal {} is out of range for int (max {})!", self.text_from(start), i64::MAX)))
+            }
+            Err(_) => Err(self.error(format!("Invalid integer literal {}!", self.text_from(start))))
+        }
+    }
+
+    // Longest match wins, so `==` is never split into two `=`
+    fn lex_symbol(&mut self) -> io::Result<()> {
+        for length in (1..=MAX_SYMBOL_LENGTH).rev() {
+            let end = self.cur + length;
+
+            if end > self.source.len() {
+                continue;
+            }
+
+            let text: String = self.source[self.cur..end].iter().collect();
+
+            if let Some(token) = get_token(&text) {
+                self.advance_by(length);
+                self.add_token(token);
+
+                return Ok(());
+            }
+        }
+
+        Err(self.error(format!("Unknown symbol '{}'!", self.source[self.cur])))
+    }
+
+    fn skip_line(&mut self) {
+        while let Some(c) = self.peek() {
+            if c == '\n' {
+                break;
+            }
+
+            self.advance();
+        }
+    }
+
+    // Skips a block comment, nested /* */ pairs have to be balanced
+    fn skip_block_comment(&mut self) -> io::Result<()> {
+        let start_line = self.current_line_pos;
+        let start_column = self.current_column_pos;
+        let mut depth = 0;
+
+        while self.peek().is_some() {
+            if self.starts_with(BLOCK_COMMENT_START) {
+                depth += 1;
+                self.advance_by(2);
+            } else if self.starts_with(BLOCK_COMMENT_END) {
+                depth -= 1;
+                self.advance_by(2);
+
+                if depth == 0 {
+                    return Ok(());
                 }
+            } else {
+                self.advance();
+            }
+        }
 
-                LexerState::Identifier => {
-                    if current_char.is_alphabetic() || current_char.is_numeric() || current_char == '_' {
-                        buffer.push(current_char);
-                    } else {
-                        self.add_token_text(buffer.as_str());
-                        buffer.clear();
-                        state = LexerState::None;
+        self.current_line_pos = start_line;
+        self.current_column_pos = start_column;
+        Err(self.error(String::from("Unterminated block comment!")))
+    }
 
-                        continue;
-                    }
+    // Consecutive doc comment lines are merged into a single token
+    fn lex_doc_comment(&mut self) {
+        self.advance_by(DOC_COMMENT.len());
+
+        let start = self.cur;
+        self.skip_line();
+
+        let line = self.text_from(start);
+        let line = line.strip_prefix(' ').unwrap_or(&line).trim_end().to_string();
+
+        if let Some(Token::DocComment(doc)) = self.tokens.last_mut() {
+            doc.push('\n');
+            doc.push_str(&line);
+        } else {
+            self.add_token(Token::DocComment(line));
+        }
+    }
+
+    fn lex_escape(&mut self) -> io::Result<char> {
+        let escape = match self.advance() {
+            Some(escape) => escape,
+            None => return Err(self.error(String::from("Unterminated escape sequence!")))
+        };
+
+        match escape {
+            'n' => Ok('\n'),
+            't' => Ok('\t'),
+            'r' => Ok('\r'),
+            '0' => Ok('\0'),
+            '\\' => Ok('\\'),
+            '"' => Ok('"'),
+            'u' => {
+                if !self.match_char('{') {
+                    return Err(self.error(String::from("Expected { after \\u in unicode escape!")));
                 }
 
-                LexerState::Symbol => {
-                    if is_symbol(current_char) && cur != line.len() {
-                        buffer.push(current_char);
-                    } else {
-                        // Checking if the symbol is a comment and if it is indeed then break the loop to proceed to the next line
-                        // easy 
-                        if buffer.contains(COMMENT) {
-                            break;
-                        }
-
-                        self.get_symbols(&buffer);
-                        buffer.clear();
-                        state = LexerState::None;
-                        
-                        continue;
+                let mut code = String::new();
+
+                while let Some(c) = self.advance() {
+                    if c == '}' {
+                        let character = u32::from_str_radix(&code, 16).ok().and_then(char::from_u32);
+
+                        return match character {
+                            Some(character) => Ok(character),
+                            None => Err(self.error(format!("Invalid unicode escape \\u{{{}}}!", code)))
+                        };
                     }
+
+                    code.push(c);
                 }
+
+                Err(self.error(String::from("Unterminated unicode escape!")))
+            }
+            _ => Err(self.error(format!("Unknown escape sequence \\{}!", escape)))
+        }
+    }
+
+    // Lexes "..." and """...""" strings, the opening quotes are already consumed
+    fn lex_string(&mut self, multiline: bool) -> io::Result<()> {
+        let start_line = self.current_line_pos;
+        let mut buffer = String::new();
+
+        loop {
+            if multiline && self.starts_with("\"\"\"") {
+                self.advance_by(3);
+                break;
             }
 
-            cur += 1;
+            let current_char = match self.peek() {
+                Some('\n') if !multiline => {
+                    return Err(self.error(String::from("Unterminated string literal, use \"\"\" for multi-line strings!")));
+                }
+                Some(current_char) => current_char,
+                None => return Err(self.error(String::from("Unterminated string literal!")))
+            };
+
+            self.advance();
+
+            match current_char {
+                '"' if !multiline => break,
+                '\\' => {
+                    let character = self.lex_escape()?;
+                    buffer.push(character);
+                },
+                _ => buffer.push(current_char)
+            }
         }
 
+        self.add_token_at(Token::StringLiteral(buffer), start_line);
+
         Ok(())
     }
 
-    fn get_symbols(&mut self, buffer: &str) {
-        let chars: Vec<char> = buffer.chars().collect();
-        let mut i = 0;
-    
-        while i < chars.len() {
-            let mut matched = false;
-
-            for j in (i + 1..=chars.len()).rev() {
-                let slice: String = chars[i..j].iter().collect();
-    
-                if let Some(token) = get_token(&slice) {
-                    self.add_token(token);
-                    i = j;
-                    matched = true;
-                    break;
-                }
+    // Lexes r"..." and r#"..."# strings, the `r` prefix is already consumed
+    fn lex_raw_string(&mut self) -> io::Result<()> {
+        let start_line = self.current_line_pos;
+        let mut hashes = 0;
+
+        while self.match_char('#') {
+            hashes += 1;
+        }
+
+        if !self.match_char('"') {
+            return Err(self.error(String::from("Expected \" to start a raw string!")));
+        }
+
+        let terminator = format!("\"{}", "#".repeat(hashes));
+        let mut buffer = String::new();
+
+        loop {
+            if self.starts_with(&terminator) {
+                self.advance_by(terminator.len());
+                break;
             }
 
-            if !matched {
-                let single = chars[i].to_string();
-                if let Some(token) = get_token(&single) {
-                    self.add_token(token);
-                }
-    
-                i += 1;
+            match self.advance() {
+                Some(current_char) => buffer.push(current_char),
+                None => return Err(self.error(String::from("Unterminated raw string literal!")))
             }
         }
+
+        self.add_token_at(Token::StringLiteral(buffer), start_line);
+
+        Ok(())
     }
 
-    pub fn lexer(&mut self) -> io::Result<()> {
-        for line in self.source_lines.clone() {
-            let line = line.clone();
-            
-            self.lex_line(line)?;
+    // Lexes f"..." strings, every {expression} part is lexed by a separate lexer on the same line
+    fn lex_format_string(&mut self) -> io::Result<()> {
+        let mut parts: Vec<FormatToken> = Vec::new();
+        let mut buffer = String::new();
 
-            self.current_line_pos += 1;
+        loop {
+            let current_char = match self.peek() {
+                Some('\n') | None => return Err(self.error(String::from("Unterminated format string literal!"))),
+                Some(current_char) => current_char
+            };
+
+            self.advance();
+
+            match current_char {
+                '"' => break,
+                '\\' => {
+                    let character = self.lex_escape()?;
+                    buffer.push(character);
+                },
+                '{' if self.match_char('{') => buffer.push('{'),
+                '}' if self.match_char('}') => buffer.push('}'),
+                '{' => {
+                    if !buffer.is_empty() {
+                        parts.push(FormatToken::Literal(buffer.clone()));
+                        buffer.clear();
+                    }
+
+                    let expression_column = self.current_column_pos;
+                    let expression = self.format_expression_source()?;
+
+                    let mut lexer = Lexer {
+                        tokens: Vec::new(),
+                        token_pos: Vec::new(),
+                        source: expression,
+                        source_path: self.source_path.clone(),
+                        current_line_pos: self.current_line_pos,
+                        current_column_pos: expression_column,
+                        token_column_pos: expression_column,
+                        cur: 0
+                    };
+                    lexer.lexer()?;
+
+                    if lexer.tokens.is_empty() {
+                        return Err(self.error(String::from("Empty expression in format string!")));
+                    }
+
+                    parts.push(FormatToken::Expression(lexer.tokens));
+                },
+                '}' => return Err(self.error(String::from("Unmatched } in format string, use }} for a literal brace!"))),
+                _ => buffer.push(current_char)
+            }
+        }
+
+        if !buffer.is_empty() {
+            parts.push(FormatToken::Literal(buffer));
         }
 
+        self.add_token(Token::FormatString(parts));
+
         Ok(())
     }
+
+    // Collects the source of an interpolated expression up to its closing brace
+    fn format_expression_source(&mut self) -> io::Result<Vec<char>> {
+        let mut expression: Vec<char> = Vec::new();
+        let mut depth = 0;
+        let mut in_string = false;
+
+        loop {
+            let current_char = match self.peek() {
+                Some('\n') | None => return Err(self.error(String::from("Unterminated expression in format string!"))),
+                Some(current_char) => current_char
+            };
+
+            self.advance();
+
+            if in_string {
+                if current_char == '\\' {
+                    if let Some(escaped) = self.peek() {
+                        expression.push(current_char);
+                        expression.push(escaped);
+                        self.advance();
+                        continue;
+                    }
+                } else if current_char == '"' {
+                    in_string = false;
+                }
+            } else {
+                match current_char {
+                    '"' => in_string = true,
+                    '{' => depth += 1,
+                    '}' if depth == 0 => return Ok(expression),
+                    '}' => depth -= 1,
+                    _ => {}
+                }
+            }
+
+            expression.push(current_char);
+        }
+    }
 }
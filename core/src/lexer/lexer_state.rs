@@ -1,8 +0,0 @@
-#[derive(Debug)]
-pub enum LexerState {
-    None,
-    String,
-    Number,
-    Identifier,
-    Symbol
-}
\ No newline at end of file
@@ -1,4 +1,3 @@
 pub mod lexer;
 pub mod token;
-pub mod lexer_state;
 pub mod token_pos;
\ No newline at end of file
@@ -2,17 +2,30 @@ use std::fmt;
 
 const SYMBOLS: &str = "()[]{}<>;:=+-*/!.,^&|/?";
 
+pub const MAX_SYMBOL_LENGTH: usize = 3;
+
 pub const COMMENT: &str = "//";
+pub const DOC_COMMENT: &str = "///";
+pub const BLOCK_COMMENT_START: &str = "/*";
+pub const BLOCK_COMMENT_END: &str = "*/";
 
 pub fn get_token(text: &str) -> Option<Token> {
     match text {
         "let" => Some(Token::Let),
+        "const" => Some(Token::Const),
+        "mut" => Some(Token::Mut),
         "function" => Some(Token::Function),
         "if" => Some(Token::If),
         "else" => Some(Token::Else),
         "while" => Some(Token::While),
         "for" => Some(Token::For),
         "return" => Some(Token::Return),
+        "try" => Some(Token::Try),
+        "impl" => Some(Token::Impl),
+        "interface" => Some(Token::Interface),
+        "catch" => Some(Token::Catch),
+        "throw" => Some(Token::Throw),
+        "yield" => Some(Token::Yield),
         "int" => Some(Token::Int),
         "float" => Some(Token::Float),
         "string" => Some(Token::String),
@@ -24,6 +37,7 @@ pub fn get_token(text: &str) -> Option<Token> {
         "struct" => Some(Token::Struct),
         "enum" => Some(Token::Enum),
         "in" => Some(Token::In),
+        "as" => Some(Token::As),
         "use" => Some(Token::Use),
         "pub" => Some(Token::Pub),
 
@@ -55,8 +69,14 @@ pub fn get_token(text: &str) -> Option<Token> {
         ">=" => Some(Token::GreaterEqual),
         "&&" => Some(Token::And),
         "||" => Some(Token::Or),
+        "&" => Some(Token::Ampersand),
+        "|" => Some(Token::Pipe),
         "?" => Some(Token::Question),
+        "??" => Some(Token::QuestionQuestion),
+        "?." => Some(Token::QuestionDot),
         "." => Some(Token::Dot),
+        ".." => Some(Token::DotDot),
+        "..=" => Some(Token::DotDotEqual),
         "::" => Some(Token::ColonColon),
         _ => None
     }
@@ -66,22 +86,38 @@ pub fn is_symbol(c: char) -> bool {
     SYMBOLS.contains(c)
 }
 
+#[derive(Debug, PartialEq, Clone)]
+pub enum FormatToken {
+    Literal(String),
+    Expression(Vec<Token>)
+}
+
 #[derive(Debug, PartialEq, Clone)]
 pub enum Token {
     Identifier(String),
     StringLiteral(String),
-    IntegerLiteral(i32),
+    IntegerLiteral(i64),
     FloatLiteral(f64),
+    FormatString(Vec<FormatToken>),
+    DocComment(String),
 
     // Keywords
 
     Let,
+    Const,
+    Mut,
     Function,
     If,
     Else,
     While,
     For,
     Return,
+    Try,
+    Impl,
+    Interface,
+    Catch,
+    Throw,
+    Yield,
     Int,
     Float,
     String,
@@ -93,6 +129,7 @@ pub enum Token {
     Struct,
     Enum,
     In,
+    As,
     Use,
     Pub,
 
@@ -126,8 +163,14 @@ pub enum Token {
     GreaterEqual, // >=
     And, // &&
     Or, // ||
+    Ampersand, // &
+    Pipe, // |
     Question, // ?
+    QuestionQuestion, // ??
+    QuestionDot, // ?.
     Dot, // .
+    DotDot, // ..
+    DotDotEqual, // ..=
     ColonColon, // ::
 }
 
@@ -138,14 +181,24 @@ impl fmt::Display for Token {
             Token::StringLiteral(string_literal) => write!(f, "string literal ({})", string_literal),
             Token::IntegerLiteral(number) => write!(f, "integer literal ({})", number),
             Token::FloatLiteral(number) => write!(f, "float literal ({})", number),
+            Token::FormatString(_) => write!(f, "format string"),
+            Token::DocComment(_) => write!(f, "doc comment"),
             
             Token::Let => write!(f, "let"),
+            Token::Const => write!(f, "const"),
+            Token::Mut => write!(f, "mut"),
             Token::Function => write!(f, "function"),
             Token::If => write!(f, "if"),
             Token::Else => write!(f, "else"),
             Token::While => write!(f, "while"),
             Token::For => write!(f, "for"),
             Token::Return => write!(f, "return"),
+            Token::Try => write!(f, "try"),
+            Token::Impl => write!(f, "impl"),
+            Token::Interface => write!(f, "interface"),
+            Token::Catch => write!(f, "catch"),
+            Token::Throw => write!(f, "throw"),
+            Token::Yield => write!(f, "yield"),
             Token::Int => write!(f, "int"),
             Token::Float => write!(f, "float"),
             Token::String => write!(f, "string"),
@@ -157,6 +210,7 @@ impl fmt::Display for Token {
             Token::Struct => write!(f, "struct"),
             Token::Enum => write!(f, "enum"),
             Token::In => write!(f, "in"),
+            Token::As => write!(f, "as"),
             Token::Use => write!(f, "use"),
             Token::Pub => write!(f, "pub"),
 
@@ -188,8 +242,14 @@ impl fmt::Display for Token {
             Token::GreaterEqual => write!(f, ">="),
             Token::And => write!(f, "&&"),
             Token::Or => write!(f, "||"),
+            Token::Ampersand => write!(f, "&"),
+            Token::Pipe => write!(f, "|"),
             Token::Question => write!(f, "?"),
+            Token::QuestionQuestion => write!(f, "??"),
+            Token::QuestionDot => write!(f, "?."),
             Token::Dot => write!(f, "."),
+            Token::DotDot => write!(f, ".."),
+            Token::DotDotEqual => write!(f, "..="),
             Token::ColonColon => write!(f, "::")
         }
     }
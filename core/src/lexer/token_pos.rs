@@ -1,14 +1,16 @@
 #[derive(Debug, Clone)]
 pub struct TokenPos {
     source: Option<String>,
-    line: i32
+    line: i32,
+    column: i32
 }
 
 impl TokenPos {
-    pub fn from(source: Option<String>, line: i32) -> TokenPos {
+    pub fn from(source: Option<String>, line: i32, column: i32) -> TokenPos {
         TokenPos { 
             source: source, 
-            line: line
+            line: line,
+            column
         }
     }
 
@@ -19,4 +21,8 @@ impl TokenPos {
     pub fn get_line(&self) -> &i32 {
         &self.line
     }
+
+    pub fn get_column(&self) -> &i32 {
+        &self.column
+    }
 }
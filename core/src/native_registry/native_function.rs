@@ -7,21 +7,82 @@ use crate::parser::data_type::DataType;
 #[derive(Debug, Clone)]
 pub struct NativeFunction {
     pub function: fn(args: Vec<Value>) -> io::Result<Value>,
-    pub return_type: DataType
+    pub return_type: DataType,
+    // Natives with a signature are checked by the analyzer like script functions
+    pub parameters: Option<Vec<DataType>>,
+    // Function types a native without a signature can be passed as, like `function(string): int` for `len`
+    pub signatures: Vec<DataType>,
+    // Arguments whose type implements `Display` are converted to strings before the call
+    pub display_arguments: bool
 }
 
 impl NativeFunction {
     pub fn from(function: fn(args: Vec<Value>) -> io::Result<Value>, return_type: DataType) -> NativeFunction {
         NativeFunction { 
             function: function, 
-            return_type: return_type 
+            return_type: return_type,
+            parameters: None,
+            signatures: Vec::new(),
+            display_arguments: false
+        }
+    }
+
+    pub fn typed(function: fn(args: Vec<Value>) -> io::Result<Value>, parameters: Vec<DataType>, return_type: DataType) -> NativeFunction {
+        NativeFunction {
+            parameters: Some(parameters),
+            ..NativeFunction::from(function, return_type)
         }
     }
     
     pub fn process(function: fn(args: Vec<Value>) -> io::Result<Value>) -> NativeFunction {
         NativeFunction {
             function,
-            return_type: DataType::void()
+            return_type: DataType::void(),
+            parameters: None,
+            signatures: Vec::new(),
+            display_arguments: false
+        }
+    }
+
+    // Each signature is a list of parameter types, the return type is the one of the native
+    pub fn with_signatures(self, signatures: Vec<Vec<DataType>>) -> NativeFunction {
+        let signatures = signatures.into_iter()
+            .map(|parameters| NativeFunction::function_type(parameters, self.return_type.clone()))
+            .collect();
+
+        NativeFunction {
+            signatures,
+            ..self
+        }
+    }
+
+    // How the analyzer and the runtime see the native as a value
+    pub fn data_type(&self) -> DataType {
+        match &self.parameters {
+            Some(parameters) => NativeFunction::function_type(parameters.clone(), self.return_type.clone()),
+            None => DataType::RustFunction {
+                return_type: Box::new(self.return_type.clone()),
+                signatures: self.signatures.clone()
+            }
+        }
+    }
+
+    fn function_type(parameters: Vec<DataType>, return_type: DataType) -> DataType {
+        let mut signature = parameters.clone();
+        signature.push(return_type.clone());
+
+        DataType::Function {
+            type_parameters: DataType::Tuple(signature).generics(),
+            parameters,
+            mutable_parameters: Vec::new(),
+            return_type: Box::new(return_type)
+        }
+    }
+
+    pub fn printer(function: fn(args: Vec<Value>) -> io::Result<Value>) -> NativeFunction {
+        NativeFunction {
+            display_arguments: true,
+            ..NativeFunction::process(function)
         }
     }
 }
@@ -1,6 +1,6 @@
 use std::cell::RefCell;
 use std::rc::Rc;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::io;
 
 use super::data_type::DataType;
@@ -8,21 +8,43 @@ use super::data_type::DataType;
 #[derive(Debug, Clone)]
 pub struct AnalyzerEnviroment {
     parent: Option<Rc<RefCell<AnalyzerEnviroment>>>,
-    variables: HashMap<String, DataType>
+    variables: HashMap<String, DataType>,
+    narrowed: HashMap<String, DataType>,
+    immutable: HashMap<String, String>,
+    // Variables of this scope that a nested function assigns, a call can change them at any time
+    captured: HashSet<String>,
+    // The scope of a function's parameters, everything above it is captured by the function
+    function: bool,
+    // Only used in the global scope, interfaces are visible everywhere
+    interfaces: HashMap<String, Vec<(String, DataType)>>,
+    // Like the methods of an `impl`, an implementation is visible until the end of its block
+    implementations: Vec<(String, DataType)>
 }
 
 impl AnalyzerEnviroment {
     pub fn new() -> AnalyzerEnviroment {
         AnalyzerEnviroment { 
             parent: None, 
-            variables: HashMap::new() 
+            variables: HashMap::new(),
+            narrowed: HashMap::new(),
+            immutable: HashMap::new(),
+            captured: HashSet::new(),
+            function: false,
+            interfaces: HashMap::new(),
+            implementations: Vec::new()
         }
     }
 
     pub fn with_parent(parent: Rc<RefCell<AnalyzerEnviroment>>) -> AnalyzerEnviroment {
         AnalyzerEnviroment {
             parent: Some(parent),
-            variables: HashMap::new()
+            variables: HashMap::new(),
+            narrowed: HashMap::new(),
+            immutable: HashMap::new(),
+            captured: HashSet::new(),
+            function: false,
+            interfaces: HashMap::new(),
+            implementations: Vec::new()
         }
     }
 
@@ -38,7 +60,9 @@ impl AnalyzerEnviroment {
     }
 
     pub fn get_variable_type(&self, name: &str) -> io::Result<DataType> {
-        if let Some(data_type) = self.variables.get(name) {
+        if let Some(data_type) = self.narrowed.get(name) {
+            Ok(data_type.clone())
+        } else if let Some(data_type) = self.variables.get(name) {
             Ok(data_type.clone())
         } else if let Some(parent) = &self.parent {
             parent.borrow().get_variable_type(name)
@@ -50,7 +74,156 @@ impl AnalyzerEnviroment {
         }
     }
 
+    pub fn get_declared_type(&self, name: &str) -> io::Result<DataType> {
+        if let Some(data_type) = self.variables.get(name) {
+            Ok(data_type.clone())
+        } else if let Some(parent) = &self.parent {
+            parent.borrow().get_declared_type(name)
+        } else {
+            Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Variable {} not found", name)
+            ))
+        }
+    }
+
     pub fn add(&mut self, name: String, data_type: DataType) {
+        self.narrowed.remove(&name);
+        self.immutable.remove(&name);
+        self.captured.remove(&name);
         self.variables.insert(name, data_type);
     }
+
+    // Like `add`, but a name can only be declared once per scope, inner scopes may still shadow it
+    pub fn declare(&mut self, name: String, data_type: DataType) -> io::Result<()> {
+        if self.variables.contains_key(&name) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Variable {} is already declared in this scope!", name)
+            ));
+        }
+
+        self.add(name, data_type);
+        Ok(())
+    }
+
+    pub fn declares(&self, name: &str) -> bool {
+        self.variables.contains_key(name)
+    }
+
+    // Whether a scope between this one and the global scope already declares the name
+    pub fn shadows(&self, name: &str) -> bool {
+        let Some(parent) = &self.parent else {
+            return false;
+        };
+
+        let parent = parent.borrow();
+        parent.parent.is_some() && (parent.declares(name) || parent.shadows(name))
+    }
+
+    pub fn declare_immutable(&mut self, name: String, data_type: DataType, kind: &str) -> io::Result<()> {
+        self.declare(name.clone(), data_type)?;
+        self.immutable.insert(name, kind.to_string());
+
+        Ok(())
+    }
+
+    // What kind of immutable binding the variable is, `None` if it can be assigned
+    pub fn get_immutable_kind(&self, name: &str) -> Option<String> {
+        if self.variables.contains_key(name) {
+            self.immutable.get(name).cloned()
+        } else if let Some(parent) = &self.parent {
+            parent.borrow().get_immutable_kind(name)
+        } else {
+            None
+        }
+    }
+
+    pub fn declare_interface(&mut self, name: String) -> io::Result<()> {
+        if self.interfaces.contains_key(&name) {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, format!("Interface {} is already declared!", name)));
+        }
+
+        self.interfaces.insert(name, Vec::new());
+        Ok(())
+    }
+
+    pub fn define_interface(&mut self, name: String, methods: Vec<(String, DataType)>) {
+        self.interfaces.insert(name, methods);
+    }
+
+    pub fn get_interface(&self, name: &str) -> Option<&Vec<(String, DataType)>> {
+        self.interfaces.get(name)
+    }
+
+    pub fn add_implementation(&mut self, interface: String, data_type: DataType) -> io::Result<()> {
+        if self.implements(&interface, &data_type) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Type {} already implements {}!", data_type, interface)
+            ));
+        }
+
+        self.implementations.push((interface, data_type));
+        Ok(())
+    }
+
+    pub fn implements(&self, interface: &str, data_type: &DataType) -> bool {
+        if self.implementations.iter().any(|(name, implementor)| name == interface && implementor == data_type) {
+            return true;
+        }
+
+        match &self.parent {
+            Some(parent) => parent.borrow().implements(interface, data_type),
+            None => false
+        }
+    }
+
+    pub fn mark_function(&mut self) {
+        self.function = true;
+    }
+
+    // Records an assignment, `nested` tells whether a function boundary was crossed on the way to the declaration
+    pub fn assign(&mut self, name: &str, nested: bool) {
+        if self.variables.contains_key(name) {
+            if nested {
+                self.captured.insert(name.to_string());
+            }
+        } else if let Some(parent) = &self.parent {
+            parent.borrow_mut().assign(name, nested || self.function);
+        }
+    }
+
+    pub fn is_captured(&self, name: &str) -> bool {
+        if self.variables.contains_key(name) {
+            self.captured.contains(name)
+        } else if let Some(parent) = &self.parent {
+            parent.borrow().is_captured(name)
+        } else {
+            false
+        }
+    }
+
+    // Returns the previous narrowing, so a temporary one can be undone
+    pub fn narrow(&mut self, name: String, data_type: DataType) -> Option<DataType> {
+        self.narrowed.insert(name, data_type)
+    }
+
+    pub fn restore_narrowing(&mut self, name: String, previous: Option<DataType>) {
+        match previous {
+            Some(data_type) => self.narrowed.insert(name, data_type),
+            None => self.narrowed.remove(&name)
+        };
+    }
+
+    // Drops the narrowing in every scope up to the one that declared the variable
+    pub fn forget_narrowing(&mut self, name: &str) {
+        self.narrowed.remove(name);
+
+        if !self.variables.contains_key(name) {
+            if let Some(parent) = &self.parent {
+                parent.borrow_mut().forget_narrowing(name);
+            }
+        }
+    }
 }
@@ -6,4 +6,11 @@ pub enum Context {
         return_data: DataType
     },
     None
-}
\ No newline at end of file
+}
+// What the body of the function being parsed does, a `yield` turns it into a generator
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BodyFlow {
+    pub yields: bool,
+    pub returns_value: bool,
+    pub returns_empty: bool
+}
@@ -1,4 +1,6 @@
+use std::collections::HashMap;
 use std::fmt::Display;
+use std::io;
 
 use super::parameter::Parameter;
 
@@ -9,14 +11,27 @@ pub enum DataType {
     Float,
     String,
     Bool,
-    Void(Option<Box<DataType>>),
+    Void,
+    Null,
+    Nullable(Box<DataType>),
     List(Box<DataType>),
     Tuple(Vec<DataType>),
+    Map(Box<DataType>, Box<DataType>),
+    Set(Box<DataType>),
+    Range,
+    Generic(String),
+    // Any type with an explicit `impl` of the interface, the value keeps its own type at runtime
+    Interface(String),
     RustFunction {
-        return_type: Box<DataType>
+        return_type: Box<DataType>,
+        // The function types a native without a signature can still be passed as
+        signatures: Vec<DataType>
     },
     Function {
+        type_parameters: Vec<String>,
         parameters: Vec<DataType>,
+        // Positions of the `mut` parameters, the function may write through them
+        mutable_parameters: Vec<usize>,
         return_type: Box<DataType>
     }
 }
@@ -35,10 +50,16 @@ impl DataType {
             DataType::Float => format!("float"),
             DataType::String => format!("string"),
             DataType::Bool => format!("boolean"),
-            DataType::Void(_) => format!("void"),
-            DataType::RustFunction{ return_type } => format!("rust_function( ... ) -> {:?}", return_type),
-            DataType::List(data_type) => format!("list {:?}", data_type),
-            DataType::Function{ parameters, return_type } => format!("function({:?}) -> {:?}", parameters, return_type),
+            DataType::Void => format!("void"),
+            DataType::Null => "null".to_string(),
+            DataType::Nullable(data_type) => format!("{}?", data_type),
+            DataType::RustFunction{ return_type, .. } => format!("rust_function( ... ) -> {:?}", return_type),
+            DataType::List(data_type) => format!("[{}]", data_type),
+            DataType::Map(key_type, value_type) => format!("{{{}: {}}}", key_type, value_type),
+            DataType::Set(data_type) => format!("{{{}}}", data_type),
+            DataType::Range => "range".to_string(),
+            DataType::Generic(name) | DataType::Interface(name) => name,
+            DataType::Function{ parameters, return_type, .. } => format!("function({:?}) -> {:?}", parameters, return_type),
             DataType::Tuple(types) => {
                 let mut out = String::new();
                 out.push('(');
@@ -71,11 +92,229 @@ impl DataType {
         out
     }
 
+    pub fn mutable_parameters(parameters: &[Parameter]) -> Vec<usize> {
+        parameters.iter()
+            .enumerate()
+            .filter(|(_, parameter)| parameter.mutable)
+            .map(|(i, _)| i)
+            .collect()
+    }
+
     pub fn void() -> DataType {
-        DataType::Void(None)
+        DataType::Void
     }
 
     pub fn is_void(data_type: &DataType) -> bool {
-        matches!(data_type, DataType::Void(_))
+        matches!(data_type, DataType::Void)
+    }
+
+    pub fn nullable(data_type: DataType) -> DataType {
+        match data_type {
+            DataType::Nullable(_) | DataType::Null => data_type,
+            data_type => DataType::Nullable(Box::new(data_type))
+        }
+    }
+
+    pub fn is_nullable(data_type: &DataType) -> bool {
+        matches!(data_type, DataType::Nullable(_) | DataType::Null)
+    }
+
+    // Whether a value of type `other` can be stored where `self` is expected. At runtime a value keeps its
+    // concrete type, so any non-null value fits an interface here, the analyzer checks the implementation
+    pub fn accepts(&self, other: &DataType) -> bool {
+        self.accepts_where(other, &|_, _| true)
+    }
+
+    // Like `accepts`, `implements` tells whether a type has an `impl` of an interface, at any depth of the type
+    pub fn accepts_where(&self, other: &DataType, implements: &dyn Fn(&str, &DataType) -> bool) -> bool {
+        if self == other {
+            return true;
+        }
+
+        match (self, other) {
+            (DataType::Nullable(_), DataType::Null) => true,
+            (DataType::Nullable(inner), DataType::Nullable(other)) => inner.accepts_where(other, implements),
+            (DataType::Nullable(inner), other) => inner.accepts_where(other, implements),
+            (DataType::List(inner), DataType::List(other))
+            | (DataType::Set(inner), DataType::Set(other)) => inner.accepts_where(other, implements),
+            (DataType::Map(key_type, value_type), DataType::Map(other_key, other_value)) => {
+                key_type == other_key && value_type.accepts_where(other_value, implements)
+            },
+            (DataType::Tuple(types), DataType::Tuple(others)) => {
+                types.len() == others.len() && types.iter().zip(others).all(|(data_type, other)| data_type.accepts_where(other, implements))
+            },
+            (DataType::Interface(interface), other) => {
+                !matches!(other, DataType::Void | DataType::Null | DataType::Nullable(_)) && implements(interface, other)
+            },
+            // A generic function fits wherever its type parameters can be bound to the expected types
+            (DataType::Function { .. }, DataType::Function { type_parameters, .. }) if !type_parameters.is_empty() => {
+                other.unify(self, type_parameters, &mut HashMap::new()).is_ok()
+            },
+            // The parameters of a function type have to match exactly, the result may be any type that fits
+            (
+                DataType::Function { type_parameters, parameters, mutable_parameters, return_type },
+                DataType::Function { type_parameters: other_type_parameters, parameters: other_parameters, mutable_parameters: other_mutable, return_type: other_return }
+            ) => {
+                type_parameters == other_type_parameters
+                    && parameters == other_parameters
+                    && mutable_parameters == other_mutable
+                    && return_type.accepts_where(other_return, implements)
+            },
+            // Natives check their own arguments, so only a declared signature can stand for them
+            (DataType::Function { .. }, DataType::RustFunction { signatures, .. }) => {
+                signatures.iter().any(|signature| self.accepts_where(signature, implements))
+            },
+            _ => false
+        }
+    }
+
+    // The narrowest type both values fit into, `null` and `int` meet at `int?`
+    pub fn common(left: &DataType, right: &DataType) -> Option<DataType> {
+        if left.accepts(right) {
+            return Some(left.clone());
+        }
+
+        if right.accepts(left) {
+            return Some(right.clone());
+        }
+
+        match (left, right) {
+            (DataType::Null, other) | (other, DataType::Null) => Some(DataType::nullable(other.clone())),
+            (DataType::Nullable(inner), other) | (other, DataType::Nullable(inner)) => {
+                DataType::common(inner, other).map(DataType::nullable)
+            },
+            (DataType::Tuple(types), DataType::Tuple(others)) if types.len() == others.len() => {
+                let types: Option<Vec<DataType>> = types.iter()
+                    .zip(others)
+                    .map(|(data_type, other)| DataType::common(data_type, other))
+                    .collect();
+
+                types.map(DataType::Tuple)
+            },
+            _ => None
+        }
+    }
+
+    // Methods are stored like functions, under a name no identifier can collide with
+    pub fn method_name(&self, method: &str) -> String {
+        format!("{}.{}", self, method)
+    }
+
+    pub fn is_map_key(data_type: &DataType) -> bool {
+        matches!(data_type, DataType::Int | DataType::String | DataType::Bool)
+    }
+
+    // Names of the type parameters used anywhere inside the type
+    pub fn generics(&self) -> Vec<String> {
+        let mut names = Vec::new();
+        self.collect_generics(&mut names);
+
+        names
+    }
+
+    pub fn is_generic(&self) -> bool {
+        !self.generics().is_empty()
+    }
+
+    fn collect_generics(&self, names: &mut Vec<String>) {
+        match self {
+            DataType::Generic(name) if !names.contains(name) => names.push(name.clone()),
+            DataType::Nullable(inner) | DataType::List(inner) | DataType::Set(inner) => inner.collect_generics(names),
+            DataType::Map(key_type, value_type) => {
+                key_type.collect_generics(names);
+                value_type.collect_generics(names);
+            },
+            DataType::Tuple(types) => {
+                for data_type in types {
+                    data_type.collect_generics(names);
+                }
+            },
+            DataType::Function { parameters, return_type, .. } => {
+                for parameter in parameters {
+                    parameter.collect_generics(names);
+                }
+
+                return_type.collect_generics(names);
+            },
+            _ => {}
+        }
+    }
+
+    // Binds `type_parameters` inside `self` so that it accepts a value of type `other`,
+    // any other type parameter is a fixed type and only matches itself
+    pub fn unify(&self, other: &DataType, type_parameters: &[String], bindings: &mut HashMap<String, DataType>) -> io::Result<()> {
+        match (self, other) {
+            (DataType::Generic(name), other) if type_parameters.contains(name) => {
+                let bound = match bindings.get(name) {
+                    Some(bound) => match DataType::common(bound, other) {
+                        Some(common) => common,
+                        None => return Err(io::Error::new(
+                            io::ErrorKind::InvalidData,
+                            format!("Type parameter {} cannot be both {} and {}!", name, bound, other)
+                        ))
+                    },
+                    None => other.clone()
+                };
+
+                bindings.insert(name.clone(), bound);
+                Ok(())
+            },
+            (DataType::Nullable(_), DataType::Null) => Ok(()),
+            (DataType::Nullable(inner), DataType::Nullable(other)) => inner.unify(other, type_parameters, bindings),
+            (DataType::Nullable(inner), other) => inner.unify(other, type_parameters, bindings),
+            (DataType::List(inner), DataType::List(other))
+            | (DataType::Set(inner), DataType::Set(other)) => inner.unify(other, type_parameters, bindings),
+            (DataType::Map(key_type, value_type), DataType::Map(other_key, other_value)) => {
+                key_type.unify(other_key, type_parameters, bindings)?;
+                value_type.unify(other_value, type_parameters, bindings)
+            },
+            (DataType::Tuple(types), DataType::Tuple(others)) if types.len() == others.len() => {
+                for (data_type, other) in types.iter().zip(others) {
+                    data_type.unify(other, type_parameters, bindings)?;
+                }
+
+                Ok(())
+            },
+            (
+                DataType::Function { parameters, return_type, .. },
+                DataType::Function { parameters: other_parameters, return_type: other_return, .. }
+            ) if parameters.len() == other_parameters.len() && self.is_generic() => {
+                for (parameter, other) in parameters.iter().zip(other_parameters) {
+                    parameter.unify(other, type_parameters, bindings)?;
+                }
+
+                return_type.unify(other_return, type_parameters, bindings)
+            },
+            (data_type, other) if data_type.accepts(other) => Ok(()),
+            (data_type, other) => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Expected {} got {}!", data_type, other)
+            ))
+        }
+    }
+
+    // Replaces the bound type parameters, unbound ones are left as they are
+    pub fn substitute(&self, bindings: &HashMap<String, DataType>) -> DataType {
+        match self {
+            DataType::Generic(name) => match bindings.get(name) {
+                Some(bound) => bound.clone(),
+                None => self.clone()
+            },
+            DataType::Nullable(inner) => DataType::nullable(inner.substitute(bindings)),
+            DataType::List(inner) => DataType::List(Box::new(inner.substitute(bindings))),
+            DataType::Set(inner) => DataType::Set(Box::new(inner.substitute(bindings))),
+            DataType::Map(key_type, value_type) => DataType::Map(
+                Box::new(key_type.substitute(bindings)),
+                Box::new(value_type.substitute(bindings))
+            ),
+            DataType::Tuple(types) => DataType::Tuple(types.iter().map(|data_type| data_type.substitute(bindings)).collect()),
+            DataType::Function { type_parameters, parameters, mutable_parameters, return_type } => DataType::Function {
+                type_parameters: type_parameters.clone(),
+                mutable_parameters: mutable_parameters.clone(),
+                parameters: parameters.iter().map(|parameter| parameter.substitute(bindings)).collect(),
+                return_type: Box::new(return_type.substitute(bindings))
+            },
+            data_type => data_type.clone()
+        }
     }
 }
@@ -5,17 +5,40 @@ use super::parameter::Parameter;
 
 use super::operator::Operator;
 
+#[derive(PartialEq, Debug, Clone)]
+pub enum FormatPart {
+    Literal(String),
+    Expression(Expression)
+}
+
 #[derive(PartialEq, Debug, Clone)]
 pub enum Expression {
     NullLiteral,
-    IntegerLiteral(i32),  
+    IntegerLiteral(i64),  
     FloatLiteral(f64),
     StringLiteral(String),
+    FormatString(Vec<FormatPart>),
     BooleanLiteral(bool),
     Identifier(String),
-    FunctionCall(String, Vec<Expression>),
+    FunctionCall(Box<Expression>, Vec<Expression>),
+    // `value.method(args)` on an interface value, the method is picked from the runtime type
+    MethodCall {
+        receiver: Box<Expression>,
+        name: String,
+        arguments: Vec<Expression>
+    },
+    // `a > b` calls `b.less(a)`, the operands still run in source order before the method gets them swapped
+    SwappedCall {
+        method: String,
+        left: Box<Expression>,
+        right: Box<Expression>
+    },
     ListLiteral(Vec<Expression>),
     TupleLiteral(Vec<Expression>),
+    MapLiteral(Vec<(Expression, Expression)>),
+    SetLiteral(Vec<Expression>),
+    // An empty `[]` or `{}` that got its type from the surrounding context
+    EmptyCollection(DataType),
     FunctionLiteral { 
         parameters: Vec<Parameter>, 
         return_type: DataType, 
@@ -25,9 +48,16 @@ pub enum Expression {
         left: Box<Expression>,
         index: Box<Expression>
     },
+    // `xs[start:end]`, a missing bound is the start or the end of the list or string
+    Slice {
+        left: Box<Expression>,
+        start: Option<Box<Expression>>,
+        end: Option<Box<Expression>>
+    },
     TupleIndex {
         left: Box<Expression>,
-        indeces: Vec<i32>
+        indeces: Vec<usize>,
+        null_safe: bool
     },
     BinaryOp {
         left: Box<Expression>,
@@ -42,4 +72,38 @@ pub enum Expression {
         expression: Box<Expression>,
         operator: Operator
     },
-}
\ No newline at end of file
+    // `start..end` or `start..=end`, with an optional `step n`
+    Range {
+        start: Box<Expression>,
+        end: Box<Expression>,
+        step: Option<Box<Expression>>,
+        inclusive: bool
+    },
+    // `value as int`, a conversion between primitive types
+    Cast {
+        expression: Box<Expression>,
+        data_type: DataType
+    },
+    // `int("42")`, reads a string and gives null if it is not a valid value
+    Parse {
+        expression: Box<Expression>,
+        data_type: DataType
+    },
+}
+impl Expression {
+    // How a callee is named in error messages, anything but a plain name is anonymous
+    pub fn callee_name(callee: &Expression) -> String {
+        match callee {
+            Expression::Identifier(name) => name.clone(),
+            _ => "expression".to_string()
+        }
+    }
+
+    // The interface an element was upcast to, collections of it take that type instead of the element's own
+    pub fn upcast_type(expression: &Expression) -> Option<DataType> {
+        match expression {
+            Expression::Cast { expression: _, data_type: data_type @ DataType::Interface(_) } => Some(data_type.clone()),
+            _ => None
+        }
+    }
+}
@@ -3,6 +3,7 @@ pub mod data_type;
 pub mod expression;
 pub mod statement;
 pub mod parameter;
+pub mod pattern;
 pub mod operator;
 pub mod semantic_analyzer;
 
@@ -13,6 +13,10 @@ pub enum Operator {
     NotEqual,
     And,
     Or,
+    Union,
+    Intersection,
+    In,
+    NullCoalescing,
     Tilde,
     PlusPlus,
     MinusMinus,
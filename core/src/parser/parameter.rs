@@ -5,4 +5,5 @@ use super::data_type::DataType;
 pub struct Parameter {
     pub name: String,
     pub data_type: DataType,
+    pub mutable: bool,
 }
\ No newline at end of file
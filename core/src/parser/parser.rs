@@ -1,16 +1,17 @@
 use crate::lexer::lexer::Lexer;
-use crate::lexer::token::Token;
+use crate::lexer::token::{FormatToken, Token};
 use crate::lexer::token_pos::TokenPos;
 use crate::native_registry::native_registry::NativeRegistry;
 use crate::native_registry::native_types::NativeTypes;
 
 use super::operator::Operator;
 use super::data_type::DataType;
-use super::expression::Expression;
+use super::expression::{Expression, FormatPart};
 use super::parameter::Parameter;
+use super::pattern::Pattern;
 use super::semantic_analyzer::SemanticAnalyzer;
 use super::statement::Statement;
-use super::context::Context;
+use super::context::{BodyFlow, Context};
 
 use std::io;
 use std::path::PathBuf;
@@ -21,6 +22,11 @@ pub struct Parser {
     current_token: usize,
     semantic_analyzer: SemanticAnalyzer,
     function_context: Context,
+    type_parameters: Vec<String>,
+    // Set while the next function is parsed as a method of an `impl` block
+    impl_type: Option<DataType>,
+    body_flow: BodyFlow,
+    warnings: Vec<String>
 }
 
 impl Parser {
@@ -45,7 +51,11 @@ impl Parser {
             token_pos,
             current_token: 0,
             semantic_analyzer: semantic_analyzer,
-            function_context: Context::None
+            function_context: Context::None,
+            type_parameters: Vec::new(),
+            impl_type: None,
+            body_flow: BodyFlow::default(),
+            warnings: Vec::new()
         }
     }
 
@@ -68,7 +78,11 @@ impl Parser {
             token_pos,
             current_token: 0,
             semantic_analyzer: semantic_analyzer,
-            function_context: Context::None
+            function_context: Context::None,
+            type_parameters: Vec::new(),
+            impl_type: None,
+            body_flow: BodyFlow::default(),
+            warnings: Vec::new()
         }
     }
 
@@ -81,16 +95,35 @@ impl Parser {
             },
 
             Err(e) => {
-                let pos = self.peek_pos();
+                let error = format!("{}: {}", self.location(), e.to_string());
 
-                let file = match pos.get_source() {
-                    Some(path) => path,
-                    None => "Main"
-                };
+                Err(io::Error::new(e.kind(), error))
+            }
+        }
+    }
+
+    // Diagnostics that do not stop the script, e.g. a variable shadowing an outer one
+    pub fn get_warnings(&self) -> &Vec<String> {
+        &self.warnings
+    }
 
-                let error = format!("kyryl-script: At {}:{}: {}", file, pos.get_line() + 1, e.to_string());
+    fn location(&self) -> String {
+        let pos = self.peek_pos();
 
-                Err(io::Error::new(e.kind(), error))
+        let file = match pos.get_source() {
+            Some(path) => path,
+            None => "Main"
+        };
+
+        format!("kyryl-script: At {}:{}:{}", file, pos.get_line() + 1, pos.get_column() + 1)
+    }
+
+    // Shadowing is allowed, but hiding a variable of an enclosing block is usually a mistake
+    fn warn_shadowing(&mut self, pattern: &Pattern) {
+        for name in pattern.names() {
+            if self.semantic_analyzer.shadows(name) {
+                let warning = format!("{}: warning: Variable {} shadows a variable of an outer scope!", self.location(), name);
+                self.warnings.push(warning);
             }
         }
     }
@@ -105,6 +138,7 @@ impl Parser {
             if let Some(statement) = statement {
                 statements.push(statement);
             } else {
+                self.match_token(&Token::RightBrace);
                 break;
             }
         }
@@ -134,21 +168,55 @@ impl Parser {
     }
 
     fn parse_parameter(&mut self) -> io::Result<Parameter> { 
+        let mutable = self.match_token(&Token::Mut);
         let name = self.consume_identifier()?;
-        self.consume_token(Token::Colon)?;
-        let data_type = self.parse_data_type()?;
+
+        let data_type = match &self.impl_type {
+            Some(impl_type) if name == "self" && !self.check(&Token::Colon) => impl_type.clone(),
+            _ => {
+                self.consume_token(Token::Colon)?;
+                self.parse_data_type()?
+            }
+        };
         
-        self.semantic_analyzer.save_variable(name.clone(), data_type.clone());
+        if mutable {
+            self.semantic_analyzer.save_variable(name.clone(), data_type.clone())?;
+        } else {
+            self.semantic_analyzer.save_immutable(name.clone(), data_type.clone(), "parameter")?;
+        }
 
         let parameter = Parameter {
             name: name,
-            data_type: data_type
+            data_type: data_type,
+            mutable
         };
 
         Ok(parameter)
     }
 
+    fn parse_doc_comment(&mut self) -> Option<String> {
+        let mut doc: Option<String> = None;
+
+        while let Some(Token::DocComment(text)) = self.tokens.get(self.current_token) {
+            doc = Some(match doc {
+                Some(doc) => format!("{}\n{}", doc, text),
+                None => text.clone()
+            });
+
+            self.current_token += 1;
+        }
+
+        doc
+    }
+
     pub fn parse_statement(&mut self) -> io::Result<Option<Statement>> {
+        let doc = self.parse_doc_comment();
+
+        // A doc comment at the end of a block has nothing to document
+        if doc.is_some() && (self.is_end() || self.check(&Token::RightBrace)) {
+            return Ok(None);
+        }
+
         let public = self.match_token(&Token::Pub);
 
         if let Context::Function { return_data: _ } = self.function_context {
@@ -158,14 +226,22 @@ impl Parser {
         }
         
         match self.advance() {
-            Some(Token::Let) => return Ok(Some(self.parse_variable_declaration_statement(public)?)),
+            Some(Token::Let) => return Ok(Some(self.parse_variable_declaration_statement(public, false, doc)?)),
+            Some(Token::Const) => return Ok(Some(self.parse_variable_declaration_statement(public, true, doc)?)),
             Some(Token::Return) => return Ok(Some(self.parse_return_statement()?)),
             Some(Token::If) => return Ok(Some(self.parse_if_statement()?)),
             Some(Token::While) => return Ok(Some(self.parse_while_statement()?)),
             Some(Token::For) => return Ok(Some(self.parse_for_statement()?)),
-            Some(Token::Function) => return Ok(Some(self.parse_function(public)?)),
+            Some(Token::Try) => return Ok(Some(self.parse_try_statement()?)),
+            Some(Token::Throw) => return Ok(Some(self.parse_throw_statement()?)),
+            Some(Token::Yield) => return Ok(Some(self.parse_yield_statement()?)),
+            Some(Token::Function) => return Ok(Some(self.parse_function(public, doc)?)),
             Some(Token::Use) => return Ok(Some(self.parse_use()?)),
+            Some(Token::Impl) => return Ok(Some(self.parse_impl(public)?)),
+            Some(Token::Interface) => return Ok(Some(self.parse_interface()?)),
             Some(Token::Identifier(name)) => {
+                let start = self.current_token - 1;
+
                 match self.advance() {
                     Some(Token::Equal) => return Ok(Some(self.parse_assignment_statement(name)?)),
                     Some(Token::PlusEqual) => return Ok(Some(self.parse_add_value_statment(name)?)),
@@ -175,7 +251,17 @@ impl Parser {
                         let mut indexes: Vec<Expression> = Vec::new();
                 
                         loop {
+                            // A slice is not assignable, `xs[1:]` is read again as an expression
+                            if self.check(&Token::Colon) {
+                                break;
+                            }
+
                             let index = self.parse_expression()?;
+
+                            if self.check(&Token::Colon) {
+                                break;
+                            }
+
                             self.consume_token(Token::RightSquareBracket)?;
 
                             indexes.push(index);
@@ -186,11 +272,27 @@ impl Parser {
                         }
                         
                         if self.match_token(&Token::Equal) {
-                            let value  = self.parse_expression()?;
+                            self.semantic_analyzer.check_mutable(&name)?;
+
+                            let target = indexes.iter().fold(Expression::Identifier(name.clone()), |left, index| {
+                                Expression::IdentifierIndex { left: Box::new(left), index: Box::new(index.clone()) }
+                            });
+                            let expected = self.semantic_analyzer.get_data_type(&target)?;
+
+                            let value = self.parse_expression_expecting(Some(&expected))?;
+
+                            if !self.semantic_analyzer.accepts(&expected, &self.semantic_analyzer.get_data_type(&value)?) {
+                                return Err(io::Error::new(io::ErrorKind::InvalidData, "Assigment value mismatch!"));
+                            }
+
                             self.consume_token(Token::Semicolon)?;
 
                             return Ok(Some(Statement::AssigmentIndex { name: name, index: indexes, value: value }));
                         }
+
+                        // Not an assignment, `handlers[i](x);` is an ordinary expression statement
+                        self.current_token = start;
+                        return Ok(Some(self.parse_expression_statement()?));
                     }
 
                     _ => self.back(),
@@ -205,44 +307,87 @@ impl Parser {
         Ok(Some(self.parse_expression_statement()?))
     }
 
-    pub fn parse_function(&mut self, public: bool) -> io::Result<Statement> {
+    pub fn parse_function(&mut self, public: bool, doc: Option<String>) -> io::Result<Statement> {
         let function_name = self.consume_identifier()?;
+        let function_name = match &self.impl_type {
+            Some(impl_type) => impl_type.method_name(&function_name),
+            None => function_name
+        };
+
+        let outer_type_parameters = self.type_parameters.len();
+        let type_parameters = self.parse_type_parameters()?;
+        self.type_parameters.extend(type_parameters.iter().cloned());
 
         self.consume_token(Token::LeftParenthesis)?;
 
         self.semantic_analyzer.enter_function_enviroment();
+        self.semantic_analyzer.mark_function_enviroment();
 
         let parameters = self.parse_parameters()?;
 
+        // Functions nested in a method body are ordinary functions again
+        if self.impl_type.take().is_some() && parameters.first().is_none_or(|parameter| parameter.name != "self") {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Method {} must take self as its first parameter!", function_name)
+            ));
+        }
+
         let function_type = if self.match_token(&Token::Colon) {
             self.parse_data_type()?
         } else {
             DataType::void()
         };
 
+        let parameter_types = DataType::Tuple(DataType::from_parameters(&parameters));
+
+        for type_parameter in type_parameters.iter() {
+            if !parameter_types.generics().contains(type_parameter) {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Type parameter {} is not used by the parameters of {}!", type_parameter, function_name)
+                ));
+            }
+        }
+
         self.consume_token(Token::LeftBrace)?;
         
         let function_data_type = DataType::Function { 
+            type_parameters,
             parameters: DataType::from_parameters(&parameters), 
+            mutable_parameters: DataType::mutable_parameters(&parameters),
             return_type: Box::new(function_type.clone())
         };
 
-        self.function_context = Context::Function { return_data: function_data_type.clone() };
-        let block = self.parse_block_statement()?;
-        self.function_context = Context::None;
+        // Saved inside the function as well so the body can call itself, unless a parameter shadows it
+        if !parameters.iter().any(|parameter| parameter.name == function_name) {
+            self.semantic_analyzer.save_immutable(function_name.clone(), function_data_type.clone(), "function")?;
+        }
+
+        let block = self.parse_function_body(&function_name, function_data_type.clone())?;
+
+        if !DataType::is_void(&function_type) && !Parser::always_returns(&block) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Function {} does not return a value on every path!", function_name)
+            ));
+        }
 
         self.semantic_analyzer.exit_function_enviroment()?;
+        self.type_parameters.truncate(outer_type_parameters);
 
         if public {
-            self.semantic_analyzer.global_save_variable(
+            self.semantic_analyzer.global_save_immutable(
                 function_name.clone(), 
-                function_data_type
-            );
+                function_data_type,
+                "function"
+            )?;
         } else {
-            self.semantic_analyzer.save_variable(
+            self.semantic_analyzer.save_immutable(
                 function_name.clone(), 
-                function_data_type
-            );
+                function_data_type,
+                "function"
+            )?;
         }
 
         Ok(
@@ -251,12 +396,181 @@ impl Parser {
                 public: public,
                 return_type: function_type, 
                 parameters: parameters, 
-                body: block 
+                body: block,
+                doc
             }
         )
     }
 
 
+    fn parse_impl(&mut self, public: bool) -> io::Result<Statement> {
+        // `impl Drawable for int` implements an interface, `impl int` only adds methods
+        let interface = match (self.tokens.get(self.current_token), self.tokens.get(self.current_token + 1)) {
+            (Some(Token::Identifier(name)), Some(Token::For)) => {
+                let name = name.clone();
+                self.current_token += 2;
+
+                Some(name)
+            },
+            _ => None
+        };
+
+        let data_type = self.parse_data_type()?;
+        self.consume_token(Token::LeftBrace)?;
+
+        let mut methods: Vec<Statement> = Vec::new();
+
+        loop {
+            let doc = self.parse_doc_comment();
+
+            if self.match_token(&Token::RightBrace) {
+                break;
+            }
+
+            self.consume_token(Token::Function)?;
+
+            self.impl_type = Some(data_type.clone());
+            methods.push(self.parse_function(public, doc)?);
+        }
+
+        if let Some(interface) = &interface {
+            self.check_implementation(interface, &data_type)?;
+
+            if public {
+                self.semantic_analyzer.global_add_implementation(interface.clone(), data_type.clone())?;
+            } else {
+                self.semantic_analyzer.add_implementation(interface.clone(), data_type.clone())?;
+            }
+        }
+
+        Ok(Statement::Impl { data_type, interface, public, methods })
+    }
+
+    fn check_implementation(&self, interface: &str, data_type: &DataType) -> io::Result<()> {
+        let self_type = DataType::Interface(interface.to_string());
+
+        for (method, signature) in self.semantic_analyzer.get_interface(interface)? {
+            let name = data_type.method_name(&method);
+
+            let (parameters, return_type) = match self.semantic_analyzer.get_variable(&name) {
+                Ok(DataType::Function { parameters, return_type, .. }) => (parameters, return_type),
+                _ => return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Type {} is missing method {} of {}!", data_type, method, interface)
+                ))
+            };
+
+            let (expected_parameters, expected_return) = match signature {
+                DataType::Function { parameters, return_type, .. } => (parameters, return_type),
+                _ => unreachable!()
+            };
+
+            // The interface itself stands for the implementing type, so `equals(self, other: Equal)` takes an int in `int`
+            let expected_parameters: Vec<DataType> = expected_parameters.into_iter()
+                .map(|parameter| if parameter == self_type { data_type.clone() } else { parameter })
+                .collect();
+
+            if parameters[1..] != expected_parameters[..] || return_type != expected_return {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Method {} does not match {} of {}!", name, method, interface)
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn parse_interface(&mut self) -> io::Result<Statement> {
+        let name = self.consume_identifier()?;
+        self.consume_token(Token::LeftBrace)?;
+
+        // Declared before the methods, so they can take the interface itself
+        self.semantic_analyzer.declare_interface(name.clone())?;
+        let self_type = DataType::Interface(name.clone());
+
+        let mut methods: Vec<(String, DataType)> = Vec::new();
+
+        loop {
+            self.parse_doc_comment();
+
+            if self.match_token(&Token::RightBrace) {
+                break;
+            }
+
+            self.consume_token(Token::Function)?;
+            let method = self.consume_identifier()?;
+            self.consume_token(Token::LeftParenthesis)?;
+
+            self.semantic_analyzer.enter_function_enviroment();
+            self.semantic_analyzer.mark_function_enviroment();
+            self.impl_type = Some(self_type.clone());
+            let parameters = self.parse_parameters();
+            self.impl_type = None;
+            self.semantic_analyzer.exit_function_enviroment()?;
+            let parameters = parameters?;
+
+            if parameters.first().is_none_or(|parameter| parameter.name != "self") {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Method {} must take self as its first parameter!", self_type.method_name(&method))
+                ));
+            }
+
+            let return_type = if self.match_token(&Token::Colon) {
+                self.parse_data_type()?
+            } else {
+                DataType::void()
+            };
+
+            self.consume_token(Token::Semicolon)?;
+
+            if methods.iter().any(|(declared, _)| *declared == method) {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Method {} is already declared in {}!", method, name)
+                ));
+            }
+
+            methods.push((method, DataType::Function {
+                type_parameters: Vec::new(),
+                parameters: DataType::from_parameters(&parameters[1..].to_vec()),
+                mutable_parameters: Vec::new(),
+                return_type: Box::new(return_type)
+            }));
+        }
+
+        self.semantic_analyzer.define_interface(name.clone(), methods.clone());
+
+        Ok(Statement::Interface { name, methods })
+    }
+
+    fn parse_type_parameters(&mut self) -> io::Result<Vec<String>> {
+        let mut type_parameters: Vec<String> = Vec::new();
+
+        if !self.match_token(&Token::LessThan) {
+            return Ok(type_parameters);
+        }
+
+        loop {
+            let name = self.consume_identifier()?;
+
+            if type_parameters.contains(&name) || self.type_parameters.contains(&name) {
+                return Err(io::Error::new(io::ErrorKind::InvalidData, format!("Type parameter {} is already declared!", name)));
+            }
+
+            type_parameters.push(name);
+
+            if !self.match_token(&Token::Comma) {
+                break;
+            }
+        }
+
+        self.consume_token(Token::GreaterThan)?;
+
+        Ok(type_parameters)
+    }
+
     fn parse_use(&mut self) -> io::Result<Statement> {
         let mut path_vec: Vec<String> = Vec::new();
         
@@ -300,6 +614,7 @@ impl Parser {
             // if file in the same directory, but it is not root, then they could not specify the path to this file, since these files are in the same directory
 
             let body = parser.parse_block_statement()?;
+            self.warnings.append(&mut parser.warnings);
 
             Ok(Statement::Use { 
                 file_name: file_name.to_string(), 
@@ -311,19 +626,99 @@ impl Parser {
     }
 
     fn parse_early_return(&mut self, name: String) -> io::Result<Statement> {
+        let return_type = match &self.function_context {
+            Context::Function { return_data: DataType::Function { return_type, .. } } => *return_type.clone(),
+            _ => return Err(io::Error::new(io::ErrorKind::InvalidData, "No function context for early return!"))
+        };
+
+        let data_type = self.semantic_analyzer.get_declared_type(&name)?;
+
+        if !DataType::is_nullable(&data_type) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Cannot early return on {}, {} is not nullable!", name, data_type)
+            ));
+        }
+
         let body: Option<Vec<Statement>> = if self.match_token(&Token::LeftBrace) {
-            Some(self.parse_block_statement()?)
+            self.semantic_analyzer.enter_function_enviroment();
+            let body = self.parse_block_statement()?;
+            self.semantic_analyzer.exit_function_enviroment()?;
+
+            // The body runs while the function is already returning, a generator cannot suspend there
+            if Statement::block_yields(&body) {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Cannot yield inside the early return on {}!", name)
+                ));
+            }
+
+            Some(body)
         } else {
             None
         };
 
         self.consume_token(Token::Semicolon)?;
 
+        // Without a return of its own the function gives back null, like `return;`
+        if body.as_deref().is_none_or(|body| !Parser::always_returns(body)) && !DataType::is_nullable(&return_type) {
+            if !DataType::is_void(&return_type) && !Parser::is_iterator(&return_type) {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Early return on {} must return a value of type {}!", name, return_type)
+                ));
+            }
+
+            self.body_flow.returns_empty = true;
+        }
+
+        // Past this point the function has already returned if the value was null
+        self.semantic_analyzer.narrow_variable(&name);
+
         Ok(Statement::EarlyReturn { name: name, body: body })
     }
 
+    fn parse_pattern(&mut self) -> io::Result<Pattern> {
+        if self.match_token(&Token::LeftParenthesis) {
+            let mut patterns: Vec<Pattern> = Vec::new();
+
+            loop {
+                let pattern = self.parse_pattern()?;
+                patterns.push(pattern);
+
+                if !self.match_token(&Token::Comma) {
+                    break;
+                }
+            }
+
+            self.consume_token(Token::RightParenthesis)?;
+
+            Ok(Pattern::Tuple(patterns))
+        } else if self.match_token(&Token::LeftSquareBracket) {
+            let mut patterns: Vec<Pattern> = Vec::new();
+
+            loop {
+                let pattern = self.parse_pattern()?;
+                patterns.push(pattern);
+
+                if !self.match_token(&Token::Comma) {
+                    break;
+                }
+            }
+
+            self.consume_token(Token::RightSquareBracket)?;
+
+            Ok(Pattern::List(patterns))
+        } else {
+            match self.consume_identifier()? {
+                name if name == "_" => Ok(Pattern::Wildcard),
+                name => Ok(Pattern::Identifier(name))
+            }
+        }
+    }
+
     fn parse_for_statement(&mut self) -> io::Result<Statement> {
-        let name = self.consume_identifier()?;
+        let pattern = self.parse_pattern()?;
 
         self.consume_token(Token::In)?;
         let expression = self.parse_expression()?;
@@ -331,18 +726,33 @@ impl Parser {
 
         self.semantic_analyzer.enter_function_enviroment();
 
-        match data_type {
-            DataType::List(child_data_type) => self.semantic_analyzer.save_variable(name.clone(), *child_data_type),
-            DataType::String => self.semantic_analyzer.save_variable(name.clone(), DataType::String),
+        let item_data_type = match data_type {
+            DataType::List(child_data_type) => *child_data_type,
+            DataType::String => DataType::String,
+            DataType::Map(key_type, value_type) => DataType::Tuple(vec![*key_type, *value_type]),
+            DataType::Set(child_data_type) => *child_data_type,
+            DataType::Range => DataType::Int,
+            // An iterator function gives the next element, null ends the loop
+            DataType::Function { parameters, return_type, .. } if parameters.is_empty() => match *return_type {
+                DataType::Nullable(item_data_type) => *item_data_type,
+                return_type => return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("An iterator function must return a nullable type, got {}!", return_type)
+                ))
+            },
             _ => return Err(io::Error::new(io::ErrorKind::InvalidData, "For loop statement mismatch type!"))
-        }
+        };
+
+        let kind = self.semantic_analyzer.immutable_root(&expression).map(|_| "loop variable");
+        self.warn_shadowing(&pattern);
+        self.semantic_analyzer.save_pattern(&pattern, item_data_type, kind)?;
 
         self.consume_token(Token::LeftBrace)?;
         let body = self.parse_block_statement()?;
 
         self.semantic_analyzer.exit_function_enviroment()?;
         
-        Ok(Statement::ForLoopStatement { name: name, list: expression, body: body })
+        Ok(Statement::ForLoopStatement { pattern: pattern, list: expression, body: body })
     }
 
     fn parse_expression_statement(&mut self) -> io::Result<Statement> {
@@ -355,20 +765,46 @@ impl Parser {
     }
 
     fn parse_add_value_statment(&mut self, name: String) -> io::Result<Statement> {
-        let expression = self.parse_expression()?;
-        self.consume_token(Token::Semicolon)?;
-
-        Ok(Statement::AddValue { name: name, value: expression })
+        self.parse_compound_assignment(name, Operator::Plus)
     }
 
     fn parse_remove_value_statement(&mut self, name: String) -> io::Result<Statement> {
+        self.parse_compound_assignment(name, Operator::Minus)
+    }
+
+    // `a += b` has to type as `a = a + b`, an overloaded `+` turns it into exactly that assignment
+    fn parse_compound_assignment(&mut self, name: String, operator: Operator) -> io::Result<Statement> {
+        self.semantic_analyzer.check_mutable(&name)?;
         let expression = self.parse_expression()?;
+
+        let declared_type = self.semantic_analyzer.get_declared_type(&name)?;
+        let operation = self.binary_operation(Expression::Identifier(name.clone()), operator, expression);
+        let result = self.semantic_analyzer.get_data_type(&operation)?;
+
+        if !self.semantic_analyzer.accepts(&declared_type, &result) {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "Assigment value mismatch!"));
+        }
+
+        self.semantic_analyzer.assign_variable(&name);
+
+        if !DataType::is_nullable(&result) {
+            self.semantic_analyzer.narrow_variable(&name);
+        }
+
         self.consume_token(Token::Semicolon)?;
 
-        Ok(Statement::RemoveValue { name: name, value: expression })
+        match operation {
+            Expression::BinaryOp { left: _, operator: Operator::Plus, right } => Ok(Statement::AddValue { name, value: *right }),
+            Expression::BinaryOp { left: _, operator: _, right } => Ok(Statement::RemoveValue { name, value: *right }),
+            value => Ok(Statement::Assigment { name, value })
+        }
     }
 
-    fn parse_variable_declaration_statement(&mut self, public: bool) -> io::Result<Statement> {
+    fn parse_variable_declaration_statement(&mut self, public: bool, constant: bool, doc: Option<String>) -> io::Result<Statement> {
+        if self.check(&Token::LeftParenthesis) || self.check(&Token::LeftSquareBracket) {
+            return self.parse_pattern_declaration(public, constant);
+        }
+
         let name = self.consume_identifier()?;
 
         let data_type = if self.match_token(&Token::Colon) {
@@ -378,20 +814,24 @@ impl Parser {
         };
         
         self.consume_token(Token::Equal)?;
-        let expression = self.parse_expression()?;
+        let expression = self.parse_expression_expecting(data_type.as_ref())?;
 
         let dt = self.semantic_analyzer.get_data_type(&expression)?;
-        
-        if let Some(data_type_to_check) = &data_type {
-            if dt != data_type_to_check.clone() && !DataType::is_void(&dt) {
-                return Err(io::Error::new(io::ErrorKind::InvalidData, "Different data types in expression and actual data type."));
-            } 
+        let variable_type = self.variable_type(data_type.as_ref(), &dt)?;
+
+        if !public {
+            self.warn_shadowing(&Pattern::Identifier(name.clone()));
         }
 
-        if public {
-            self.semantic_analyzer.global_save_variable(name.clone(), dt.clone());
-        } else {
-            self.semantic_analyzer.save_variable(name.clone(), dt.clone());
+        match (public, constant) {
+            (true, true) => self.semantic_analyzer.global_save_immutable(name.clone(), variable_type, "constant")?,
+            (true, false) => self.semantic_analyzer.global_save_variable(name.clone(), variable_type)?,
+            (false, true) => self.semantic_analyzer.save_immutable(name.clone(), variable_type, "constant")?,
+            (false, false) => self.semantic_analyzer.save_variable(name.clone(), variable_type)?
+        }
+
+        if !DataType::is_nullable(&dt) {
+            self.semantic_analyzer.narrow_variable(&name);
         }
 
         self.consume_token(Token::Semicolon)?;
@@ -400,47 +840,141 @@ impl Parser {
             Statement::VariableDeclaration {
                 name,
                 public,
+                constant,
                 data_type,
-                value: Some(expression)
+                value: Some(expression),
+                doc
             }
         )
     }
 
-    fn parse_return_statement(&mut self) -> io::Result<Statement> { 
-        if let Context::Function{ return_data} = self.function_context.clone() {
-            let expression = self.parse_expression()?;
-            let data_type = self.semantic_analyzer.get_data_type(&expression)?;
+    // The type a declaration binds, the annotation wins over the type of the value
+    fn variable_type(&self, data_type: Option<&DataType>, dt: &DataType) -> io::Result<DataType> {
+        match data_type {
+            Some(data_type_to_check) => {
+                if *dt == DataType::Null && !DataType::is_nullable(data_type_to_check) {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("Cannot assign null to {}, declare it as {}? to allow null!", data_type_to_check, data_type_to_check)
+                    ));
+                }
 
-            if let DataType::Function { parameters: _, return_type } = return_data {
-                if *return_type != data_type {
-                    return Err(io::Error::new(io::ErrorKind::InvalidData, "Mismatch return and function return types!"));
+                if !self.semantic_analyzer.accepts(data_type_to_check, dt) {
+                    return Err(io::Error::new(io::ErrorKind::InvalidData, "Different data types in expression and actual data type."));
                 }
 
-                self.consume_token(Token::Semicolon)?;
-                return Ok(Statement::ReturnStatement { value: Some(expression) });
+                Ok(data_type_to_check.clone())
+            },
+            None if *dt == DataType::Null => {
+                Err(io::Error::new(io::ErrorKind::InvalidData, "Cannot infer the type of null, annotate the variable with a nullable type!"))
+            },
+            None => Ok(dt.clone())
+        }
+    }
+
+    fn parse_pattern_declaration(&mut self, public: bool, constant: bool) -> io::Result<Statement> {
+        if public {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "Cannot export a destructuring declaration!"));
+        }
+
+        let pattern = self.parse_pattern()?;
+
+        let data_type = if self.match_token(&Token::Colon) {
+            Some(self.parse_data_type()?)
+        } else {
+            None
+        };
+
+        self.consume_token(Token::Equal)?;
+        let expression = self.parse_expression_expecting(data_type.as_ref())?;
+
+        let dt = self.semantic_analyzer.get_data_type(&expression)?;
+        let variable_type = self.variable_type(data_type.as_ref(), &dt)?;
+
+        self.warn_shadowing(&pattern);
+        self.semantic_analyzer.save_pattern(&pattern, variable_type, constant.then_some("constant"))?;
+
+        self.consume_token(Token::Semicolon)?;
+
+        Ok(Statement::PatternDeclaration { pattern, constant, data_type, value: expression })
+    }
+
+    // A body with a `yield` becomes a single `Generator` statement, it never runs to a missing `return`
+    fn parse_function_body(&mut self, function_name: &str, function_data_type: DataType) -> io::Result<Vec<Statement>> {
+        let outer_context = std::mem::replace(&mut self.function_context, Context::Function { return_data: function_data_type.clone() });
+        let outer_flow = std::mem::take(&mut self.body_flow);
+
+        let block = self.parse_block_statement();
+
+        self.function_context = outer_context;
+        let flow = std::mem::replace(&mut self.body_flow, outer_flow);
+        let block = block?;
+
+        let return_type = match function_data_type {
+            DataType::Function { return_type, .. } => *return_type,
+            _ => DataType::void()
+        };
+
+        if flow.yields {
+            if flow.returns_value {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Generator {} cannot return a value, yield it instead!", function_name)
+                ));
             }
+
+            return Ok(vec![Statement::Generator { body: block, data_type: return_type }]);
+        }
+
+        if flow.returns_empty && !DataType::is_void(&return_type) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Function {} must return a value of type {}!", function_name, return_type)
+            ));
+        }
+
+        Ok(block)
+    }
+
+    fn parse_return_statement(&mut self) -> io::Result<Statement> { 
+        if let Context::Function { return_data: _ } = self.function_context
+            && self.match_token(&Token::Semicolon) {
+            self.body_flow.returns_empty = true;
+
+            return Ok(Statement::ReturnStatement { value: None });
+        }
+
+        if let Context::Function{ return_data: DataType::Function { return_type, .. } } = self.function_context.clone() {
+            self.body_flow.returns_value = true;
+
+            let expression = self.parse_expression_expecting(Some(&return_type))?;
+            let data_type = self.semantic_analyzer.get_data_type(&expression)?;
+
+            if !self.semantic_analyzer.accepts(&return_type, &data_type) {
+                return Err(io::Error::new(io::ErrorKind::InvalidData, "Mismatch return and function return types!"));
+            }
+
+            self.consume_token(Token::Semicolon)?;
+            return Ok(Statement::ReturnStatement { value: Some(expression) });
         } 
 
         Err(io::Error::new(io::ErrorKind::InvalidData, "No function context for return!"))
     }
 
     fn parse_assignment_statement(&mut self, name: String) -> io::Result<Statement> {
-        let expression = self.parse_expression()?;
+        self.semantic_analyzer.check_mutable(&name)?;
+        let data_type_to_check = self.semantic_analyzer.get_declared_type(&name)?;
+        let expression = self.parse_expression_expecting(Some(&data_type_to_check))?;
         let data_type = self.semantic_analyzer.get_data_type(&expression)?;
-        let data_type_to_check = self.semantic_analyzer.get_variable(&name)?;
 
-        match data_type_to_check {
-            DataType::Void(Some (null_type)) => {
-                if *null_type != data_type && !DataType::is_void(&data_type) {
-                    return Err(io::Error::new(io::ErrorKind::InvalidData, "Assigment value mismatch!"))
-                }
-            }
+        if !self.semantic_analyzer.accepts(&data_type_to_check, &data_type) {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "Assigment value mismatch!"))
+        }
 
-            _ => {
-                if data_type_to_check != data_type && !DataType::is_void(&data_type) {
-                    return Err(io::Error::new(io::ErrorKind::InvalidData, "Assigment value mismatch!"))
-                }
-            }
+        self.semantic_analyzer.assign_variable(&name);
+
+        if !DataType::is_nullable(&data_type) {
+            self.semantic_analyzer.narrow_variable(&name);
         }
 
         self.consume_token(Token::Semicolon)?;
@@ -474,6 +1008,7 @@ impl Parser {
         self.consume_token(Token::LeftBrace)?;
 
         self.semantic_analyzer.enter_function_enviroment();
+        self.semantic_analyzer.narrow_condition(&condition, true);
         let if_body = self.parse_block_statement()?;
         self.semantic_analyzer.exit_function_enviroment()?;
 
@@ -481,6 +1016,7 @@ impl Parser {
             self.consume_token(Token::LeftBrace)?;
             
             self.semantic_analyzer.enter_function_enviroment();
+            self.semantic_analyzer.narrow_condition(&condition, false);
             let result = self.parse_block_statement()?;
             self.semantic_analyzer.exit_function_enviroment()?;
 
@@ -489,6 +1025,15 @@ impl Parser {
             None
         };
 
+        // A branch that always returns proves the opposite condition for the code after the if
+        if Parser::always_returns(&if_body) {
+            self.semantic_analyzer.narrow_condition(&condition, false);
+        }
+
+        if else_block.as_ref().is_some_and(|block| Parser::always_returns(block)) {
+            self.semantic_analyzer.narrow_condition(&condition, true);
+        }
+
         Ok(
             Statement::IfStatement {
                 condition: condition,
@@ -509,17 +1054,132 @@ impl Parser {
         self.consume_token(Token::LeftBrace)?;
 
         self.semantic_analyzer.enter_function_enviroment();
+        self.semantic_analyzer.narrow_condition(&condition, true);
         let block = self.parse_block_statement()?;
         self.semantic_analyzer.exit_function_enviroment()?;
 
+        self.semantic_analyzer.narrow_condition(&condition, false);
+
         Ok(Statement::WhileStatement {
                 condition: condition,
                 body: block
         })
     }
 
+    fn parse_try_statement(&mut self) -> io::Result<Statement> {
+        self.consume_token(Token::LeftBrace)?;
+
+        self.semantic_analyzer.enter_function_enviroment();
+        let body = self.parse_block_statement()?;
+        self.semantic_analyzer.exit_function_enviroment()?;
+
+        self.consume_token(Token::Catch)?;
+        let name = self.consume_identifier()?;
+        self.consume_token(Token::LeftBrace)?;
+
+        self.semantic_analyzer.enter_function_enviroment();
+        self.semantic_analyzer.save_variable(name.clone(), DataType::String)?;
+        let catch_body = self.parse_block_statement()?;
+        self.semantic_analyzer.exit_function_enviroment()?;
+
+        Ok(Statement::TryStatement { body, name, catch_body })
+    }
+
+    fn parse_throw_statement(&mut self) -> io::Result<Statement> {
+        let value = self.parse_expression()?;
+        let data_type = self.semantic_analyzer.get_data_type(&value)?;
+
+        if data_type != DataType::String {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, format!("Throw expects a string message, got {}!", data_type)));
+        }
+
+        self.consume_token(Token::Semicolon)?;
+
+        Ok(Statement::Throw { value })
+    }
+
+    fn parse_yield_statement(&mut self) -> io::Result<Statement> {
+        let item_type = match &self.function_context {
+            Context::Function { return_data: DataType::Function { return_type, .. } } => match return_type.as_ref() {
+                DataType::Function { parameters, return_type, .. } if parameters.is_empty() => match return_type.as_ref() {
+                    DataType::Nullable(item_type) => Some(*item_type.clone()),
+                    _ => None
+                },
+                _ => None
+            },
+            _ => return Err(io::Error::new(io::ErrorKind::InvalidData, "No function context for yield!"))
+        };
+
+        let Some(item_type) = item_type else {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "A generator must return an iterator function like function(): int?!"
+            ));
+        };
+
+        let value = self.parse_expression_expecting(Some(&item_type))?;
+        let data_type = self.semantic_analyzer.get_data_type(&value)?;
+
+        if !self.semantic_analyzer.accepts(&item_type, &data_type) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Cannot yield {} from a generator of {}!", data_type, item_type)
+            ));
+        }
+
+        self.consume_token(Token::Semicolon)?;
+        self.body_flow.yields = true;
+
+        Ok(Statement::Yield { value })
+    }
+
+    // The return type of a generator, which also ends on an empty return
+    fn is_iterator(data_type: &DataType) -> bool {
+        matches!(
+            data_type,
+            DataType::Function { parameters, return_type, .. } if parameters.is_empty() && DataType::is_nullable(return_type)
+        )
+    }
+
+    fn always_returns(block: &[Statement]) -> bool {
+        match block.last() {
+            Some(Statement::ReturnStatement { value: _ }) => true,
+            Some(Statement::Throw { value: _ }) => true,
+            Some(Statement::Generator { .. }) => true,
+            Some(Statement::TryStatement { body, name: _, catch_body }) => {
+                Parser::always_returns(body) && Parser::always_returns(catch_body)
+            },
+            Some(Statement::IfStatement { condition: _, body, else_body: Some(else_body) }) => {
+                Parser::always_returns(body) && Parser::always_returns(else_body)
+            },
+            _ => false
+        }
+    }
+
     pub fn parse_expression(&mut self) -> io::Result<Expression> {
-        self.parse_logic_or()
+        let mut expression = self.parse_null_coalescing()?;
+        self.semantic_analyzer.infer_literals(&mut expression, None);
+
+        Ok(expression)
+    }
+
+    fn parse_expression_expecting(&mut self, expected: Option<&DataType>) -> io::Result<Expression> {
+        let mut expression = self.parse_expression()?;
+        self.semantic_analyzer.infer_literals(&mut expression, expected);
+
+        Ok(expression)
+    }
+
+    fn parse_null_coalescing(&mut self) -> io::Result<Expression> {
+        let mut expression = self.parse_logic_or()?;
+
+        while self.match_token(&Token::QuestionQuestion) {
+            let right = self.parse_logic_or()?;
+
+            expression = Expression::BinaryOp { left: Box::new(expression), operator: Operator::NullCoalescing, right: Box::new(right) }
+        }
+
+        Ok(expression)
     }
 
     fn parse_logic_or(&mut self) -> io::Result<Expression> {
@@ -547,14 +1207,15 @@ impl Parser {
     }
 
     fn parse_comparison(&mut self) -> io::Result<Expression> {
-        let mut expression = self.parse_addition()?;
+        let mut expression = self.parse_range()?;
 
         while self.match_token(&Token::EqualEqual)      ||
                 self.match_token(&Token::NotEqual)    ||
                 self.match_token(&Token::GreaterEqual)  ||
                 self.match_token(&Token::LessEqual)     ||
                 self.match_token(&Token::GreaterThan)   ||
-                self.match_token(&Token::LessThan)
+                self.match_token(&Token::LessThan)      ||
+                self.match_token(&Token::In)
         {    
             let operator = match self.previous() {
                 Token::EqualEqual => Operator::EqualEqual,
@@ -563,12 +1224,58 @@ impl Parser {
                 Token::GreaterThan => Operator::Greater,
                 Token::LessEqual => Operator::LessEqual,
                 Token::LessThan => Operator::Less,
+                Token::In => Operator::In,
                 _ => unreachable!()
             };
 
+            let right = self.parse_range()?;
+
+            expression = self.binary_operation(expression, operator, right);
+        }
+
+        Ok(expression)
+    }
+
+    // `step` is only a keyword right after a range, so it stays usable as a name
+    fn parse_range(&mut self) -> io::Result<Expression> {
+        let start = self.parse_union()?;
+
+        if !self.match_token(&Token::DotDot) && !self.match_token(&Token::DotDotEqual) {
+            return Ok(start);
+        }
+
+        let inclusive = *self.previous() == Token::DotDotEqual;
+        let end = self.parse_union()?;
+
+        let step = if self.check(&Token::Identifier("step".to_string())) {
+            self.advance();
+            Some(Box::new(self.parse_union()?))
+        } else {
+            None
+        };
+
+        Ok(Expression::Range { start: Box::new(start), end: Box::new(end), step, inclusive })
+    }
+
+    fn parse_union(&mut self) -> io::Result<Expression> {
+        let mut expression = self.parse_intersection()?;
+
+        while self.match_token(&Token::Pipe) {
+            let right = self.parse_intersection()?;
+
+            expression = Expression::BinaryOp { left: Box::new(expression), operator: Operator::Union, right: Box::new(right) }
+        }
+
+        Ok(expression)
+    }
+
+    fn parse_intersection(&mut self) -> io::Result<Expression> {
+        let mut expression = self.parse_addition()?;
+
+        while self.match_token(&Token::Ampersand) {
             let right = self.parse_addition()?;
 
-            expression = Expression::BinaryOp { left: Box::new(expression), operator: operator, right: Box::new(right) }
+            expression = Expression::BinaryOp { left: Box::new(expression), operator: Operator::Intersection, right: Box::new(right) }
         }
 
         Ok(expression)
@@ -586,7 +1293,7 @@ impl Parser {
 
             let right = self.parse_multiplication()?;
 
-            expression = Expression::BinaryOp { left: Box::new(expression), operator: operator, right: Box::new(right) };
+            expression = self.binary_operation(expression, operator, right);
         }
 
         Ok(expression)
@@ -604,17 +1311,81 @@ impl Parser {
 
             let right = self.parse_power()?;
 
-            expression = Expression::BinaryOp { left: Box::new(expression), operator: operator, right: Box::new(right) };
+            expression = self.binary_operation(expression, operator, right);
         }
 
         Ok(expression)
     }
 
+    // Operators the built-in types do not support call the matching method of the receiver type,
+    // `a + b` becomes `a.add(b)` and `a > b` becomes `b.less(a)`
+    fn binary_operation(&self, left: Expression, operator: Operator, right: Expression) -> Expression {
+        let method = match operator {
+            Operator::Plus => "add",
+            Operator::Minus => "subtract",
+            Operator::Multiply => "multiply",
+            Operator::Divide => "divide",
+            Operator::Less | Operator::Greater | Operator::LessEqual | Operator::GreaterEqual => "less",
+            _ => ""
+        };
+
+        let expression = Expression::BinaryOp { left: Box::new(left), operator, right: Box::new(right) };
+
+        if method.is_empty() || self.semantic_analyzer.get_data_type(&expression).is_ok() {
+            return expression;
+        }
+
+        let Expression::BinaryOp { left, operator, right } = expression else { unreachable!() };
+
+        // `a > b` is `b.less(a)` and `a <= b` is `!b.less(a)`, so the right operand is the receiver
+        let swapped = matches!(operator, Operator::Greater | Operator::LessEqual);
+        let negated = matches!(operator, Operator::LessEqual | Operator::GreaterEqual);
+
+        let receiver = if swapped { &right } else { &left };
+
+        let method = match self.semantic_analyzer.get_data_type(receiver) {
+            Ok(data_type) => data_type.method_name(method),
+            Err(_) => return Expression::BinaryOp { left, operator, right }
+        };
+
+        if !matches!(self.semantic_analyzer.get_variable(&method), Ok(DataType::Function { .. })) {
+            return Expression::BinaryOp { left, operator, right };
+        }
+
+        let call = match swapped {
+            true => Expression::SwappedCall { method, left, right },
+            false => Expression::FunctionCall(Box::new(Expression::Identifier(method)), vec![*left, *right])
+        };
+
+        match negated {
+            true => Expression::UnaryOp { expression: Box::new(call), operator: Operator::Tilde },
+            false => call
+        }
+    }
+
+    // Indexing a type that has no built-in indexing calls its `index` method
+    fn index_operation(&self, left: Expression, index: Expression) -> Expression {
+        let expression = Expression::IdentifierIndex { left: Box::new(left), index: Box::new(index) };
+
+        if self.semantic_analyzer.get_data_type(&expression).is_ok() {
+            return expression;
+        }
+
+        let Expression::IdentifierIndex { left, index } = expression else { unreachable!() };
+
+        match self.semantic_analyzer.get_data_type(&left) {
+            Ok(data_type) if matches!(self.semantic_analyzer.get_variable(&data_type.method_name("index")), Ok(DataType::Function { .. })) => {
+                Expression::FunctionCall(Box::new(Expression::Identifier(data_type.method_name("index"))), vec![*left, *index])
+            },
+            _ => Expression::IdentifierIndex { left, index }
+        }
+    }
+
     fn parse_power(&mut self) -> io::Result<Expression> {
-        let mut expression = self.parse_unary()?;
+        let mut expression = self.parse_cast()?;
 
         while self.match_token(&Token::Power) {
-            let right = self.parse_unary()?;
+            let right = self.parse_cast()?;
 
             expression = Expression::BinaryOp { left: Box::new(expression), operator: Operator::Power, right: Box::new(right) }
         }
@@ -622,6 +1393,18 @@ impl Parser {
         Ok(expression)
     }
 
+    fn parse_cast(&mut self) -> io::Result<Expression> {
+        let mut expression = self.parse_unary()?;
+
+        while self.match_token(&Token::As) {
+            let data_type = self.parse_data_type()?;
+
+            expression = Expression::Cast { expression: Box::new(expression), data_type };
+        }
+
+        Ok(expression)
+    }
+
     fn parse_unary(&mut self) -> io::Result<Expression> {
         if self.match_token(&Token::Minus) || self.match_token(&Token::Not) {
             let operator = match self.previous() {
@@ -644,7 +1427,7 @@ impl Parser {
     }
 
     fn parse_front_unary(&mut self) -> io::Result<Expression> {
-        let left = self.parse_identifier_index()?;
+        let left = self.parse_postfix()?;
         
         if self.match_token(&Token::PlusPlus) 
             || self.match_token(&Token::MinusMinus)
@@ -668,67 +1451,140 @@ impl Parser {
         }
     }
 
-    fn parse_identifier_index(&mut self) -> io::Result<Expression> {
-        let left = self.parse_tuple_index()?;
+    // Calls, indexing and tuple access chain left to right, so `make_adder(1)(2)` and `handlers[i](x)` work
+    fn parse_postfix(&mut self) -> io::Result<Expression> {
+        let mut left = self.parse_primary()?;
 
-        if self.match_token(&Token::LeftSquareBracket) {
-            let mut index: Option<Expression> = None;
-            
-            loop {
-                let value = self.parse_expression()?;
-                
-                if let Some(i) = index {
-                    index = Some(Expression::IdentifierIndex { left: Box::new(i), index: Box::new(value) }); 
+        loop {
+            if self.match_token(&Token::LeftParenthesis) {
+                let arguments = if self.match_token(&Token::RightParenthesis) {
+                    Vec::new()
                 } else {
-                    index = Some(Expression::IdentifierIndex { left: Box::new(left.clone()), index: Box::new(value)});
+                    let arguments = self.parse_function_call_parameters()?;
+                    self.consume_token(Token::RightParenthesis)?;
+
+                    arguments
+                };
+
+                left = Expression::FunctionCall(Box::new(left), arguments);
+            } else if self.match_token(&Token::LeftSquareBracket) {
+                let index = if self.check(&Token::Colon) {
+                    None
+                } else {
+                    Some(self.parse_expression()?)
+                };
+
+                if self.match_token(&Token::Colon) {
+                    let end = if self.check(&Token::RightSquareBracket) {
+                        None
+                    } else {
+                        Some(Box::new(self.parse_expression()?))
+                    };
+
+                    self.consume_token(Token::RightSquareBracket)?;
+
+                    left = Expression::Slice { left: Box::new(left), start: index.map(Box::new), end };
+                    continue;
                 }
-                
+
                 self.consume_token(Token::RightSquareBracket)?;
-                
-                if !self.match_token(&Token::LeftSquareBracket) {
-                    break;
-                }
-            }
 
-            Ok(index.unwrap())
-        } else {
-            Ok(left)
-        }
-    }
+                // Only reachable with an index, an empty `[:` always takes the slice branch
+                left = self.index_operation(left, index.unwrap());
+            } else if self.check(&Token::Dot) && matches!(self.tokens.get(self.current_token + 1), Some(Token::Identifier(_))) {
+                self.advance();
+                let name = self.consume_identifier()?;
+                self.consume_token(Token::LeftParenthesis)?;
 
-    fn parse_tuple_index(&mut self) -> io::Result<Expression> {
-        let left = self.parse_primary()?;
+                let arguments = if self.match_token(&Token::RightParenthesis) {
+                    Vec::new()
+                } else {
+                    let arguments = self.parse_function_call_parameters()?;
+                    self.consume_token(Token::RightParenthesis)?;
 
-        if self.match_token(&Token::Dot) {
-            let mut indeces: Vec<i32> = Vec::new();
+                    arguments
+                };
 
-            loop {
-                if let Token::IntegerLiteral(index) = self.peek() {
-                    indeces.push(*index);
-                    self.advance();
-                }
+                left = self.parse_method_call(left, name, arguments)?;
+            } else if self.match_token(&Token::Dot) || self.match_token(&Token::QuestionDot) {
+                let null_safe = self.previous() == &Token::QuestionDot;
+                let mut indeces: Vec<usize> = Vec::new();
 
-                if !self.match_token(&Token::Dot) {
-                    break;
+                loop {
+                    if let Token::IntegerLiteral(index) = self.peek() {
+                        let index = usize::try_from(*index)
+                            .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "Tuple index cannot be negative!"))?;
+
+                        indeces.push(index);
+                        self.advance();
+                    }
+
+                    // `t.0.len()` leaves the last dot to the method call
+                    let next_is_index = matches!(self.tokens.get(self.current_token + 1), Some(Token::IntegerLiteral(_)));
+
+                    if !(next_is_index && self.match_token(&Token::Dot)) {
+                        break;
+                    }
                 }
+
+                left = Expression::TupleIndex { left: Box::new(left), indeces: indeces, null_safe };
+            } else {
+                break;
             }
+        }
+
+        Ok(left)
+    }
+
+    // `value.method(args)` calls the method of the value's type with the value as `self`,
+    // without one it falls back to the native of the same name, so `xs.len()` is `len(xs)`
+    fn parse_method_call(&mut self, receiver: Expression, name: String, arguments: Vec<Expression>) -> io::Result<Expression> {
+        let receiver_type = self.semantic_analyzer.get_data_type(&receiver)?;
+        self.semantic_analyzer.check_null(&receiver_type)?;
 
-            Ok(Expression::TupleIndex { left: Box::new(left), indeces: indeces })
+        if let DataType::Interface(interface) = &receiver_type {
+            self.semantic_analyzer.interface_method(interface, &name)?;
+
+            return Ok(Expression::MethodCall { receiver: Box::new(receiver), name, arguments });
+        }
 
+        let method = receiver_type.method_name(&name);
+
+        let callee = if self.semantic_analyzer.get_variable(&method).is_ok() {
+            method
+        } else if self.is_native(&name) {
+            name
         } else {
-            Ok(left)
+            return Err(io::Error::new(io::ErrorKind::InvalidData, format!("Type {} has no method {}!", receiver_type, name)));
+        };
+
+        let mut call_arguments = vec![receiver];
+        call_arguments.extend(arguments);
+
+        Ok(Expression::FunctionCall(Box::new(Expression::Identifier(callee)), call_arguments))
+    }
+
+    // Natives with a signature are typed like script functions, so the registry tells them apart
+    fn is_native(&self, name: &str) -> bool {
+        match self.semantic_analyzer.get_variable(name) {
+            Ok(DataType::RustFunction { .. }) => true,
+            Ok(DataType::Function { .. }) => NativeRegistry::get().borrow().get_native(name).is_some(),
+            _ => false
         }
     }
 
     fn parse_primary(&mut self) -> io::Result<Expression> {
         match self.advance() {
             Some(Token::True) => Ok(Expression::BooleanLiteral(true)),
-            Some(Token::False) => Ok(Expression::BooleanLiteral(true)),
+            Some(Token::False) => Ok(Expression::BooleanLiteral(false)),
             Some(Token::Function) => self.parse_expression_function(),
             Some(Token::Null) => Ok(Expression::NullLiteral),
+            Some(Token::Int) => self.parse_conversion(DataType::Int),
+            Some(Token::Float) => self.parse_conversion(DataType::Float),
             Some(Token::IntegerLiteral(value)) => Ok(Expression::IntegerLiteral(value)),
             Some(Token::FloatLiteral(value)) => Ok(Expression::FloatLiteral(value)),
             Some(Token::StringLiteral(value)) => Ok(Expression::StringLiteral(value)),
+            Some(Token::FormatString(parts)) => self.parse_format_string(parts),
 
             Some(Token::LeftParenthesis) => {
                 let expression = self.parse_expression()?;
@@ -762,6 +1618,10 @@ impl Parser {
             Some(Token::LeftSquareBracket) => {
                 let mut expressions: Vec<Expression> = Vec::new();
 
+                if self.match_token(&Token::RightSquareBracket) {
+                    return Ok(Expression::ListLiteral(expressions));
+                }
+
                 loop {
                     let expression = self.parse_expression()?;
                     expressions.push(expression);
@@ -775,30 +1635,111 @@ impl Parser {
 
                 Ok(Expression::ListLiteral(expressions))
             },
-            Some(Token::Identifier(name)) => {
-                let name = name.to_owned();
-                if self.match_token(&Token::LeftParenthesis) {
-                    if self.match_token(&Token::RightParenthesis) {
-                        return Ok(Expression::FunctionCall(name, Vec::new()));
-                    }
+            Some(Token::LeftBrace) => {
+                if self.match_token(&Token::RightBrace) {
+                    return Ok(Expression::MapLiteral(Vec::new()));
+                }
 
-                    let parameters = self.parse_function_call_parameters()?;
-                    self.consume_token(Token::RightParenthesis)?;
+                let first = self.parse_expression()?;
 
-                    Ok(Expression::FunctionCall(name, parameters))
+                if self.match_token(&Token::Colon) {
+                    self.parse_map_literal(first)
                 } else {
-                    Ok(Expression::Identifier(name))
+                    self.parse_set_literal(first)
                 }
             },
+            Some(Token::Identifier(name)) => Ok(Expression::Identifier(name)),
             None => Err(io::Error::new(io::ErrorKind::InvalidData, "Expected expression got nothing!")),
             _ => Err(io::Error::new(io::ErrorKind::InvalidData, format!("Expected expression got {}", self.peek())))
         }
     }
 
+    fn parse_conversion(&mut self, data_type: DataType) -> io::Result<Expression> {
+        self.consume_token(Token::LeftParenthesis)?;
+        let expression = self.parse_expression()?;
+        self.consume_token(Token::RightParenthesis)?;
+
+        Ok(Expression::Parse { expression: Box::new(expression), data_type })
+    }
+
+    fn parse_map_literal(&mut self, first_key: Expression) -> io::Result<Expression> {
+        let first_value = self.parse_expression()?;
+        let mut entries: Vec<(Expression, Expression)> = vec![(first_key, first_value)];
+
+        while self.match_token(&Token::Comma) {
+            let key = self.parse_expression()?;
+            self.consume_token(Token::Colon)?;
+            let value = self.parse_expression()?;
+
+            entries.push((key, value));
+        }
+
+        self.consume_token(Token::RightBrace)?;
+
+        Ok(Expression::MapLiteral(entries))
+    }
+
+    fn parse_set_literal(&mut self, first: Expression) -> io::Result<Expression> {
+        let mut expressions: Vec<Expression> = vec![first];
+
+        while self.match_token(&Token::Comma) {
+            let expression = self.parse_expression()?;
+            expressions.push(expression);
+        }
+
+        self.consume_token(Token::RightBrace)?;
+
+        Ok(Expression::SetLiteral(expressions))
+    }
+
+    fn parse_format_string(&mut self, parts: Vec<FormatToken>) -> io::Result<Expression> {
+        let pos = self.token_pos.get(self.current_token - 1).cloned();
+        let mut format_parts: Vec<FormatPart> = Vec::new();
+
+        for part in parts {
+            match part {
+                FormatToken::Literal(literal) => format_parts.push(FormatPart::Literal(literal)),
+                FormatToken::Expression(tokens) => {
+                    let token_pos = match &pos {
+                        Some(pos) => vec![pos.clone(); tokens.len()],
+                        None => Vec::new()
+                    };
+
+                    // Parse the interpolated tokens in place, so they see the same semantic scope
+                    let outer_tokens = std::mem::replace(&mut self.tokens, tokens);
+                    let outer_token_pos = std::mem::replace(&mut self.token_pos, token_pos);
+                    let outer_current_token = self.current_token;
+                    self.current_token = 0;
+
+                    let result = self.parse_format_expression();
+
+                    self.tokens = outer_tokens;
+                    self.token_pos = outer_token_pos;
+                    self.current_token = outer_current_token;
+
+                    format_parts.push(FormatPart::Expression(result?));
+                }
+            }
+        }
+
+        Ok(Expression::FormatString(format_parts))
+    }
+
+    fn parse_format_expression(&mut self) -> io::Result<Expression> {
+        let expression = self.parse_expression()?;
+
+        if !self.is_end() {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, format!("Unexpected {} in format string expression!", self.peek())));
+        }
+
+        Ok(expression)
+    }
+
     fn parse_expression_function(&mut self) -> io::Result<Expression> {
         self.consume_token(Token::LeftParenthesis)?;
 
         self.semantic_analyzer.enter_function_enviroment();
+        self.semantic_analyzer.mark_function_enviroment();
 
         let parameters = self.parse_parameters()?;
 
@@ -813,13 +1754,17 @@ impl Parser {
         self.consume_token(Token::LeftBrace)?;
 
         let function_data_type = DataType::Function { 
+            type_parameters: Vec::new(),
             parameters: DataType::from_parameters(&parameters), 
+            mutable_parameters: DataType::mutable_parameters(&parameters),
             return_type: Box::new(return_type.clone())
         };
         
-        self.function_context = Context::Function{ return_data: function_data_type.clone() };
-        let block = self.parse_block_statement()?;
-        self.function_context = Context::None;
+        let block = self.parse_function_body("literal", function_data_type.clone())?;
+
+        if !DataType::is_void(&return_type) && !Parser::always_returns(&block) {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "Function literal does not return a value on every path!"));
+        }
 
         self.semantic_analyzer.exit_function_enviroment()?;
 
@@ -838,7 +1783,17 @@ impl Parser {
         self.peek() == token
     }
 
-    fn parse_data_type(&mut self) -> io::Result<DataType> {        
+    fn parse_data_type(&mut self) -> io::Result<DataType> {
+        let data_type = self.parse_base_data_type()?;
+
+        if self.match_token(&Token::Question) {
+            return Ok(DataType::nullable(data_type));
+        }
+
+        Ok(data_type)
+    }
+
+    fn parse_base_data_type(&mut self) -> io::Result<DataType> {        
         match self.advance() {
             Some(Token::Int) => Ok(DataType::Int),
             Some(Token::Float) => Ok(DataType::Float),
@@ -867,7 +1822,7 @@ impl Parser {
                     DataType::void()
                 };
 
-                Ok(DataType::Function { parameters: parameters, return_type: Box::new(return_type) })
+                Ok(DataType::Function { type_parameters: Vec::new(), parameters: parameters, mutable_parameters: Vec::new(), return_type: Box::new(return_type) })
             }
             Some(Token::LeftSquareBracket) => {
                 let data_type = self.parse_data_type()?;
@@ -891,7 +1846,31 @@ impl Parser {
                 self.consume_token(Token::RightParenthesis)?;
 
                 Ok(DataType::Tuple(data_types))
-            }
+            },
+            Some(Token::LeftBrace) => {
+                let key_type = self.parse_data_type()?;
+
+                if self.match_token(&Token::RightBrace) {
+                    return Ok(DataType::Set(Box::new(key_type)));
+                }
+
+                self.consume_token(Token::Colon)?;
+                let value_type = self.parse_data_type()?;
+                self.consume_token(Token::RightBrace)?;
+
+                Ok(DataType::Map(Box::new(key_type), Box::new(value_type)))
+            },
+            Some(Token::Identifier(name)) => {
+                if self.type_parameters.contains(&name) {
+                    Ok(DataType::Generic(name))
+                } else if name == "range" {
+                    Ok(DataType::Range)
+                } else if self.semantic_analyzer.is_interface(&name) {
+                    Ok(DataType::Interface(name))
+                } else {
+                    Err(io::Error::new(io::ErrorKind::InvalidData, format!("Unknown type {}!", name)))
+                }
+            },
             _ => Err(io::Error::new(io::ErrorKind::InvalidData, "Cannot parse the data type!"))
         }
     }
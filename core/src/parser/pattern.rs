@@ -0,0 +1,18 @@
+#[derive(PartialEq, Debug, Clone)]
+pub enum Pattern {
+    Identifier(String),
+    Wildcard,
+    Tuple(Vec<Pattern>),
+    List(Vec<Pattern>)
+}
+
+impl Pattern {
+    // Every name the pattern binds, wildcards bind nothing
+    pub fn names(&self) -> Vec<&String> {
+        match self {
+            Pattern::Identifier(name) => vec![name],
+            Pattern::Wildcard => Vec::new(),
+            Pattern::Tuple(patterns) | Pattern::List(patterns) => patterns.iter().flat_map(Pattern::names).collect()
+        }
+    }
+}
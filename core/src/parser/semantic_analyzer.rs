@@ -1,13 +1,16 @@
 use std::cell::RefCell;
+use std::collections::HashMap;
 use std::rc::Rc;
 use std::io;
 
 use crate::native_registry::native_function::NativeFunction;
+use crate::native_registry::native_registry::NativeRegistry;
 use crate::parser::operator::Operator;
 
 use super::analyzer_enviroment::AnalyzerEnviroment;
 use super::data_type::DataType;
-use super::expression::Expression;
+use super::expression::{Expression, FormatPart};
+use super::pattern::Pattern;
 
 #[derive(Debug, Clone)]
 pub struct SemanticAnalyzer {
@@ -19,6 +22,8 @@ pub struct SemanticAnalyzer {
 impl SemanticAnalyzer {
     pub fn new() -> SemanticAnalyzer {
         let global = Rc::new(RefCell::new(AnalyzerEnviroment::new()));
+        SemanticAnalyzer::define_builtin_interfaces(&mut global.borrow_mut());
+
         let local = Rc::new(RefCell::new(AnalyzerEnviroment::with_parent(global.clone())));
 
         SemanticAnalyzer {
@@ -40,8 +45,70 @@ impl SemanticAnalyzer {
         self.global.clone()
     } 
 
+    // `Display` is used when a value is printed or converted to a string, `Equal` by `==` and `!=`
+    fn define_builtin_interfaces(global: &mut AnalyzerEnviroment) {
+        let method = |parameters: Vec<DataType>, return_type: DataType| DataType::Function {
+            type_parameters: Vec::new(),
+            parameters,
+            mutable_parameters: Vec::new(),
+            return_type: Box::new(return_type)
+        };
+
+        global.define_interface("Display".to_string(), vec![
+            ("to_string".to_string(), method(Vec::new(), DataType::String))
+        ]);
+        global.define_interface("Equal".to_string(), vec![
+            ("equals".to_string(), method(vec![DataType::Interface("Equal".to_string())], DataType::Bool))
+        ]);
+    }
+
+    pub fn declare_interface(&mut self, name: String) -> io::Result<()> {
+        self.global.borrow_mut().declare_interface(name)
+    }
+
+    pub fn define_interface(&mut self, name: String, methods: Vec<(String, DataType)>) {
+        self.global.borrow_mut().define_interface(name, methods);
+    }
+
+    pub fn get_interface(&self, name: &str) -> io::Result<Vec<(String, DataType)>> {
+        match self.global.borrow().get_interface(name) {
+            Some(methods) => Ok(methods.clone()),
+            None => Err(io::Error::new(io::ErrorKind::InvalidData, format!("Unknown interface {}!", name)))
+        }
+    }
+
+    pub fn is_interface(&self, name: &str) -> bool {
+        self.global.borrow().get_interface(name).is_some()
+    }
+
+    pub fn interface_method(&self, interface: &str, name: &str) -> io::Result<DataType> {
+        self.get_interface(interface)?
+            .into_iter()
+            .find(|(method, _)| method == name)
+            .map(|(_, signature)| signature)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, format!("Interface {} has no method {}!", interface, name)))
+    }
+
+    pub fn add_implementation(&mut self, interface: String, data_type: DataType) -> io::Result<()> {
+        self.local.borrow_mut().add_implementation(interface, data_type)
+    }
+
+    pub fn global_add_implementation(&mut self, interface: String, data_type: DataType) -> io::Result<()> {
+        self.global.borrow_mut().add_implementation(interface, data_type)
+    }
+
+    pub fn implements(&self, interface: &str, data_type: &DataType) -> bool {
+        self.local.borrow().implements(interface, data_type)
+    }
+
+    // `DataType::accepts` with the implementations visible here, so `[Drawable]` only takes lists of implementations
+    pub fn accepts(&self, expected: &DataType, actual: &DataType) -> bool {
+        expected.accepts_where(actual, &|interface, data_type| self.implements(interface, data_type))
+    }
+
+    // Natives live in the global scope, so scripts can shadow them like any outer name
     pub fn register_rust_function(&mut self, name: String, function: &NativeFunction) {
-        self.local.borrow_mut().add(name, DataType::RustFunction { return_type: Box::new(function.return_type.clone()) });
+        self.global.borrow_mut().add(name, function.data_type());
     }
 
     pub fn get_variable(&self, name: &str) -> io::Result<DataType> {
@@ -49,9 +116,14 @@ impl SemanticAnalyzer {
         env.get_variable_type(name)
     }
 
+    pub fn get_declared_type(&self, name: &str) -> io::Result<DataType> {
+        let env = self.local.borrow();
+        env.get_declared_type(name)
+    }
+
     pub fn check_null(&self, data_type: &DataType) -> io::Result<()> {
         match data_type {
-            DataType::Void(_) => Err(io::Error::new(io::ErrorKind::InvalidData, "Attempt to perform an operation with a null value")),
+            DataType::Void | DataType::Null | DataType::Nullable(_) => Err(io::Error::new(io::ErrorKind::InvalidData, "Attempt to perform an operation with a null value")),
             _ => Ok(())
         }
     }
@@ -61,6 +133,11 @@ impl SemanticAnalyzer {
         self.local = Rc::new(RefCell::new(AnalyzerEnviroment::with_parent(parent.clone())));
     }
 
+    // Marks the scope just entered as the parameters scope of a function or closure
+    pub fn mark_function_enviroment(&mut self) {
+        self.local.borrow_mut().mark_function();
+    }
+
     pub fn exit_function_enviroment(&mut self) -> io::Result<()> {
         let new_env = {
             let local = self.local.clone();
@@ -123,7 +200,7 @@ impl SemanticAnalyzer {
     }
 
     fn comparison(&self, left: DataType, right: DataType) -> io::Result<DataType> {
-        if left == right {
+        if left.accepts(&right) || right.accepts(&left) || left == DataType::Null || right == DataType::Null {
             Ok(DataType::Bool)
         } else {
             Err(io::Error::new(io::ErrorKind::InvalidData, "Arithmetic type error!"))
@@ -143,9 +220,60 @@ impl SemanticAnalyzer {
         }
     }
     
+    fn null_coalescing(&self, left: DataType, right: DataType) -> io::Result<DataType> {
+        let inner = match left {
+            DataType::Nullable(inner) => *inner,
+            DataType::Null => return Ok(right),
+            data_type => data_type
+        };
+
+        if inner.accepts(&right) {
+            Ok(inner)
+        } else if DataType::nullable(inner.clone()).accepts(&right) {
+            Ok(DataType::nullable(inner))
+        } else {
+            Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Null coalescing type mismatch, expected {} got {}!", inner, right)
+            ))
+        }
+    }
+
+    fn set_operation(&self, left: DataType, right: DataType) -> io::Result<DataType> {
+        match (left, right) {
+            (DataType::Set(left), DataType::Set(right)) if left == right => Ok(DataType::Set(left)),
+            _ => Err(io::Error::new(io::ErrorKind::InvalidData, "Set type error!"))
+        }
+    }
+
+    fn membership(&self, left: DataType, right: DataType) -> io::Result<DataType> {
+        let matches = match &right {
+            DataType::List(child_type) | DataType::Set(child_type) => **child_type == left,
+            DataType::Map(key_type, _) => **key_type == left,
+            DataType::String => left == DataType::String,
+            DataType::Range => left == DataType::Int,
+            _ => false
+        };
+
+        if matches {
+            Ok(DataType::Bool)
+        } else {
+            Err(io::Error::new(io::ErrorKind::InvalidData, format!("Cannot check if {} is in {}!", left, right)))
+        }
+    }
+    
     fn binary_operation(&self, operator: &Operator, left: DataType, right: DataType) -> io::Result<DataType> {
         match operator {
             Operator::Plus => self.plus(left, right),
+
+            Operator::Minus if matches!(left, DataType::Set(_)) => self.set_operation(left, right),
+
+            Operator::Union
+            | Operator::Intersection => self.set_operation(left, right),
+
+            Operator::In => self.membership(left, right),
+
+            Operator::NullCoalescing => self.null_coalescing(left, right),
             
             Operator::Minus
             | Operator::Multiply
@@ -186,19 +314,53 @@ impl SemanticAnalyzer {
         }
     }
 
+    fn cast(&self, from: DataType, to: &DataType) -> io::Result<DataType> {
+        self.check_null(&from)?;
+
+        match (&from, to) {
+            (from, to) if from == to => Ok(to.clone()),
+            (DataType::Int, DataType::Float)
+            | (DataType::Float, DataType::Int)
+            | (DataType::Bool, DataType::Int)
+            | (DataType::Int | DataType::Float | DataType::Bool, DataType::String) => Ok(to.clone()),
+            (DataType::String, DataType::Int | DataType::Float) => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Cannot cast string to {}, use {}(...) to parse it!", to, to)
+            )),
+            (from, DataType::String) if self.implements("Display", from) => Ok(to.clone()),
+            (from, DataType::Interface(interface)) => {
+                if !self.implements(interface, from) {
+                    return Err(io::Error::new(io::ErrorKind::InvalidData, format!("Type {} does not implement {}!", from, interface)));
+                }
+
+                Ok(to.clone())
+            },
+            _ => Err(io::Error::new(io::ErrorKind::InvalidData, format!("Cannot cast {} to {}!", from, to)))
+        }
+    }
+
     fn identefier_index(&self, left: DataType, index: DataType) -> io::Result<DataType> {
         match (left, index) {
             (DataType::List(children_type), DataType::Int) => Ok(*children_type),
+            (DataType::String, DataType::Int) => Ok(DataType::String),
+            (DataType::Map(key_type, value_type), index) => {
+                if *key_type != index {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("Map key type mismatch, expected {} got {}!", key_type, index)
+                    ));
+                }
+
+                Ok(*value_type)
+            },
             _ => Err(io::Error::new(io::ErrorKind::InvalidData, "Invalid data in list indexing operation!"))
         }
     }
 
-    fn tuple_index(&self, mut left: DataType, indeces: &Vec<i32>) -> io::Result<DataType> {
-        for index in indeces {
-            let index = *index as usize;
-
+    fn tuple_index(&self, mut left: DataType, indeces: &Vec<usize>) -> io::Result<DataType> {
+        for &index in indeces {
             if let DataType::Tuple(children) = &left {
-                if index > children.len() {
+                if index >= children.len() {
                     return Err(io::Error::new(io::ErrorKind::InvalidData, "Tuple out of index!"))
                 }
                 
@@ -214,8 +376,12 @@ impl SemanticAnalyzer {
     pub fn get_data_type(&self, expression: &Expression) -> io::Result<DataType> {
         match expression {
             Expression::BinaryOp { left, operator, right } => {
+                let right = match operator {
+                    Operator::And => self.get_narrowed_data_type(right, left, true)?,
+                    Operator::Or => self.get_narrowed_data_type(right, left, false)?,
+                    _ => self.get_data_type(right)?
+                };
                 let left = self.get_data_type(left)?;
-                let right = self.get_data_type(right)?;
 
                 self.binary_operation(operator, left, right)
             },
@@ -230,72 +396,238 @@ impl SemanticAnalyzer {
                 self.front_unary_operation(operator, left)
             },
 
+            Expression::Cast { expression, data_type } => {
+                let from = self.get_data_type(expression)?;
+                self.cast(from, data_type)
+            },
+
+            Expression::Range { start, end, step, .. } => {
+                let bounds = [Some(start), Some(end), step.as_ref()];
+
+                for bound in bounds.into_iter().flatten() {
+                    let data_type = self.get_data_type(bound)?;
+
+                    if data_type != DataType::Int {
+                        return Err(io::Error::new(io::ErrorKind::InvalidData, format!("Range bounds must be int, got {}!", data_type)));
+                    }
+                }
+
+                Ok(DataType::Range)
+            },
+
+            Expression::Parse { expression, data_type } => {
+                match self.get_data_type(expression)? {
+                    DataType::String => Ok(DataType::nullable(data_type.clone())),
+                    other => Err(io::Error::new(io::ErrorKind::InvalidData, format!("{}() expects a string, got {}!", data_type, other)))
+                }
+            },
+
             Expression::ListLiteral(children) => {
-                if children.len() == 0 {
-                    return Err(io::Error::new(io::ErrorKind::InvalidData, "List empty!"));
+                if children.is_empty() {
+                    return Err(io::Error::new(io::ErrorKind::InvalidData, "Cannot infer the type of an empty list, annotate it!"));
                 }
 
-                let first = self.get_data_type(&children[0].clone())?;
+                let mut first = self.get_data_type(&children[0].clone())?;
 
                 for child in children.iter() {
                     let child = self.get_data_type(&child.clone())?;
 
-                    if first == child {
-                        continue;
+                    match DataType::common(&first, &child) {
+                        Some(common) => first = common,
+                        None => return Err(io::Error::new(io::ErrorKind::InvalidData, "Children has different types in list!"))
                     }
-
-                    return Err(io::Error::new(io::ErrorKind::InvalidData, "Children has different types in list!"));
                 }
 
                 Ok(DataType::List(Box::new(first)))
             },
 
-            Expression::TupleIndex { left, indeces } => {
+            Expression::MapLiteral(entries) => {
+                if entries.is_empty() {
+                    return Err(io::Error::new(io::ErrorKind::InvalidData, "Cannot infer the type of an empty map or set, annotate it!"));
+                }
+
+                let key_type = self.get_data_type(&entries[0].0)?;
+                let mut value_type = self.get_data_type(&entries[0].1)?;
+
+                if !DataType::is_map_key(&key_type) {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("Map key has to be int, string or boolean, got {}!", key_type)
+                    ));
+                }
+
+                for (key, value) in entries.iter() {
+                    if self.get_data_type(key)? != key_type {
+                        return Err(io::Error::new(io::ErrorKind::InvalidData, "Keys has different types in map!"));
+                    }
+
+                    match DataType::common(&value_type, &self.get_data_type(value)?) {
+                        Some(common) => value_type = common,
+                        None => return Err(io::Error::new(io::ErrorKind::InvalidData, "Values has different types in map!"))
+                    }
+                }
+
+                Ok(DataType::Map(Box::new(key_type), Box::new(value_type)))
+            },
+
+            Expression::SetLiteral(children) => {
+                let first = self.get_data_type(&children[0])?;
+
+                if !DataType::is_map_key(&first) {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("Set element has to be int, string or boolean, got {}!", first)
+                    ));
+                }
+
+                for child in children.iter() {
+                    if self.get_data_type(child)? != first {
+                        return Err(io::Error::new(io::ErrorKind::InvalidData, "Elements has different types in set!"));
+                    }
+                }
+
+                Ok(DataType::Set(Box::new(first)))
+            },
+
+            Expression::EmptyCollection(data_type) => Ok(data_type.clone()),
+
+            Expression::TupleIndex { left, indeces, null_safe } => {
                 let left = self.get_data_type(&left)?;
 
-                self.tuple_index(left, indeces)
+                match left {
+                    DataType::Nullable(inner) if *null_safe => Ok(DataType::nullable(self.tuple_index(*inner, indeces)?)),
+                    DataType::Null if *null_safe => Ok(DataType::Null),
+                    left => {
+                        self.check_null(&left)?;
+                        self.tuple_index(left, indeces)
+                    }
+                }
             },
             
             Expression::Identifier(name) => {
                 match self.local.borrow().get_variable_type(name) {
-                    Ok(DataType::Void(_)) => Ok(DataType::void()),
-                    Ok(data_type) => Ok(data_type.clone()),
+                    Ok(data_type) => Ok(data_type),
                     Err(_) => Err(io::Error::new(io::ErrorKind::InvalidData, format!("Variable {} not found!", name)))
                 }
             },
 
-            Expression::FunctionCall(name, call_parameters) => {
-                let function = self.get_variable(name)?;
+            Expression::SwappedCall { method, left, right } => {
+                let callee = Box::new(Expression::Identifier(method.clone()));
+
+                self.get_data_type(&Expression::FunctionCall(callee, vec![*right.clone(), *left.clone()]))
+            },
+
+            Expression::FunctionCall(callee, call_parameters) => {
+                let name = Expression::callee_name(callee);
+                let function = self.get_data_type(callee)?;
 
                 match function {
-                    DataType::RustFunction { return_type } => {
+                    DataType::RustFunction { return_type, .. } => {
+                        // Natives check their own arguments, but the arguments still have to be valid expressions
+                        for call_parameter in call_parameters {
+                            self.get_data_type(call_parameter)?;
+                        }
+
                         Ok(*return_type)
                     }
 
-                    DataType::Function { parameters, return_type } => {
+                    DataType::Function { type_parameters, parameters, mutable_parameters, return_type } => {
+                        if call_parameters.len() != parameters.len() {
+                            return Err(io::Error::new(
+                                io::ErrorKind::InvalidData,
+                                format!("Function {} expects {} arguments, got {}!", name, parameters.len(), call_parameters.len())
+                            ));
+                        }
+
+                        for &i in mutable_parameters.iter() {
+                            if let Some((kind, root)) = self.immutable_root(&call_parameters[i]) {
+                                return Err(io::Error::new(
+                                    io::ErrorKind::InvalidData,
+                                    format!("Cannot pass {} {} to a mut parameter of {}!", kind, root, name)
+                                ));
+                            }
+                        }
+
+                        let mut bindings: HashMap<String, DataType> = HashMap::new();
+
                         for (call_parameter, parameter) in call_parameters.iter().zip(parameters) {
                             let call_parameter = self.get_data_type(call_parameter)?;
 
-                            if call_parameter != parameter && !DataType::is_void(&call_parameter) {
-                                return Err(io::Error::new(io::ErrorKind::InvalidData, "Function signature mismatch"));
+                            if !parameter.is_generic() {
+                                if !self.accepts(&parameter, &call_parameter) {
+                                    return Err(io::Error::new(io::ErrorKind::InvalidData, "Function signature mismatch"));
+                                }
+
+                                continue;
+                            }
+
+                            if let Err(e) = parameter.unify(&call_parameter, &type_parameters, &mut bindings) {
+                                return Err(io::Error::new(io::ErrorKind::InvalidData, format!("Cannot call {}: {}", name, e)));
                             }
                         }
 
-                        Ok(*return_type)
+                        Ok(return_type.substitute(&bindings))
                     }
 
-                    DataType::Void(_) => Err(io::Error::new(io::ErrorKind::InvalidData, "Ти далбайоб?")),
-                    _ => Err(io::Error::new(io::ErrorKind::InvalidData, format!("Function {} not found!", name)))
+                    DataType::Void => Err(io::Error::new(io::ErrorKind::InvalidData, "Ти далбайоб?")),
+                    data_type => Err(io::Error::new(io::ErrorKind::InvalidData, format!("Cannot call {} of type {}!", name, data_type)))
                 }
             },
 
+            Expression::MethodCall { receiver, name, arguments } => {
+                let interface = match self.get_data_type(receiver)? {
+                    DataType::Interface(interface) => interface,
+                    data_type => return Err(io::Error::new(io::ErrorKind::InvalidData, format!("Type {} is not an interface!", data_type)))
+                };
+
+                let (parameters, return_type) = match self.interface_method(&interface, name)? {
+                    DataType::Function { parameters, return_type, .. } => (parameters, return_type),
+                    _ => unreachable!()
+                };
+
+                if arguments.len() != parameters.len() {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("Function {}.{} expects {} arguments, got {}!", interface, name, parameters.len(), arguments.len())
+                    ));
+                }
+
+                for (argument, parameter) in arguments.iter().zip(parameters) {
+                    if !self.accepts(&parameter, &self.get_data_type(argument)?) {
+                        return Err(io::Error::new(io::ErrorKind::InvalidData, "Function signature mismatch"));
+                    }
+                }
+
+                Ok(*return_type)
+            },
+
             Expression::IdentifierIndex { left, index } => {
                 let left = self.get_data_type(left)?;
+                self.check_null(&left)?;
+
                 let index_type = self.get_data_type(index)?;
 
                 self.identefier_index(left, index_type)
             },
 
+            Expression::Slice { left, start, end } => {
+                let left = self.get_data_type(left)?;
+                self.check_null(&left)?;
+
+                for bound in [start, end].into_iter().flatten() {
+                    let data_type = self.get_data_type(bound)?;
+
+                    if data_type != DataType::Int {
+                        return Err(io::Error::new(io::ErrorKind::InvalidData, format!("Slice bounds must be int, got {}!", data_type)));
+                    }
+                }
+
+                match left {
+                    DataType::List(_) | DataType::String => Ok(left),
+                    left => Err(io::Error::new(io::ErrorKind::InvalidData, format!("Cannot slice {}, only lists and strings!", left)))
+                }
+            },
+
             Expression::TupleLiteral(expressions) => {
                 let mut data_types: Vec<DataType> = Vec::new();
 
@@ -313,21 +645,352 @@ impl SemanticAnalyzer {
                     data_types.push(parameter.data_type.clone());
                 }
                 
-                Ok(DataType::Function { parameters: data_types, return_type: Box::new(return_type.clone()) })
+                Ok(DataType::Function {
+                    type_parameters: Vec::new(),
+                    parameters: data_types,
+                    mutable_parameters: DataType::mutable_parameters(parameters),
+                    return_type: Box::new(return_type.clone())
+                })
             },
             Expression::IntegerLiteral(_) => Ok(DataType::Int),
             Expression::FloatLiteral(_) => Ok(DataType::Float),
             Expression::StringLiteral(_) => Ok(DataType::String),
+            Expression::FormatString(parts) => {
+                for part in parts {
+                    if let FormatPart::Expression(expression) = part {
+                        let data_type = self.get_data_type(expression)?;
+
+                        if let DataType::Function { .. } | DataType::RustFunction { .. } = data_type {
+                            return Err(io::Error::new(io::ErrorKind::InvalidData, format!("Cannot format value of type {}!", data_type)));
+                        }
+                    }
+                }
+
+                Ok(DataType::String)
+            },
             Expression::BooleanLiteral(_) => Ok(DataType::Bool),
-            Expression::NullLiteral => Ok(DataType::void())
+            Expression::NullLiteral => Ok(DataType::Null)
+        }
+    }
+
+    // Pushes the type expected by the context down into literals, an empty `[]` or `{}`
+    // cannot type itself and is only resolved this way
+    pub fn infer_literals(&self, expression: &mut Expression, expected: Option<&DataType>) {
+        let expected = match expected {
+            Some(DataType::Nullable(inner)) => Some(inner.as_ref()),
+            expected => expected
+        };
+
+        // Where an interface is expected a concrete value is upcast, the cast checks the implementation
+        if let Some(interface @ DataType::Interface(_)) = expected {
+            self.infer_literals(expression, None);
+
+            if let Ok(data_type) = self.get_data_type(expression)
+                && !DataType::is_nullable(&data_type)
+                && data_type != *interface {
+                let inner = std::mem::replace(expression, Expression::NullLiteral);
+                *expression = Expression::Cast { expression: Box::new(inner), data_type: interface.clone() };
+            }
+
+            return;
+        }
+
+        match expression {
+            Expression::ListLiteral(children) if children.is_empty() => {
+                if let Some(data_type @ DataType::List(_)) = expected {
+                    *expression = Expression::EmptyCollection(data_type.clone());
+                }
+            },
+            Expression::MapLiteral(entries) if entries.is_empty() => {
+                if let Some(data_type @ (DataType::Map(..) | DataType::Set(_))) = expected {
+                    *expression = Expression::EmptyCollection(data_type.clone());
+                }
+            },
+            Expression::ListLiteral(children) => {
+                let child_type = match expected {
+                    Some(DataType::List(child_type)) => Some(child_type.as_ref()),
+                    _ => None
+                };
+
+                for child in children {
+                    self.infer_literals(child, child_type);
+                }
+            },
+            Expression::MapLiteral(entries) => {
+                let value_type = match expected {
+                    Some(DataType::Map(_, value_type)) => Some(value_type.as_ref()),
+                    _ => None
+                };
+
+                for (key, value) in entries {
+                    self.infer_literals(key, None);
+                    self.infer_literals(value, value_type);
+                }
+            },
+            Expression::TupleLiteral(children) => {
+                let types = match expected {
+                    Some(DataType::Tuple(types)) if types.len() == children.len() => Some(types),
+                    _ => None
+                };
+
+                for (i, child) in children.iter_mut().enumerate() {
+                    self.infer_literals(child, types.map(|types| &types[i]));
+                }
+            },
+            Expression::SetLiteral(children) => {
+                for child in children {
+                    self.infer_literals(child, None);
+                }
+            },
+            Expression::BinaryOp { left, operator, right } => {
+                self.infer_literals(left, None);
+
+                // `xs ?? []` gets the type of `xs` without the null
+                let right_type = match (operator, expected) {
+                    (Operator::NullCoalescing, Some(expected)) => Some(expected.clone()),
+                    (Operator::NullCoalescing, None) => match self.get_data_type(left) {
+                        Ok(DataType::Nullable(inner)) => Some(*inner),
+                        _ => None
+                    },
+                    _ => None
+                };
+
+                self.infer_literals(right, right_type.as_ref());
+            },
+            Expression::UnaryOp { expression, .. }
+            | Expression::FrontUnaryOp { expression, .. }
+            | Expression::Cast { expression, .. }
+            | Expression::Parse { expression, .. } => self.infer_literals(expression, None),
+            Expression::FunctionCall(callee, arguments) => {
+                self.infer_literals(callee, None);
+
+                let parameters = match self.get_data_type(callee) {
+                    Ok(DataType::Function { parameters, .. }) => parameters,
+                    _ => Vec::new()
+                };
+
+                for (i, argument) in arguments.iter_mut().enumerate() {
+                    let parameter = parameters.get(i).filter(|parameter| !parameter.is_generic());
+                    self.infer_literals(argument, parameter);
+                }
+            },
+            Expression::MethodCall { receiver, name, arguments } => {
+                self.infer_literals(receiver, None);
+
+                let parameters = match self.get_data_type(receiver) {
+                    Ok(DataType::Interface(interface)) => match self.interface_method(&interface, name) {
+                        Ok(DataType::Function { parameters, .. }) => parameters,
+                        _ => Vec::new()
+                    },
+                    _ => Vec::new()
+                };
+
+                for (i, argument) in arguments.iter_mut().enumerate() {
+                    self.infer_literals(argument, parameters.get(i));
+                }
+            },
+            Expression::IdentifierIndex { left, index: right }
+            | Expression::SwappedCall { left, right, .. } => {
+                self.infer_literals(left, None);
+                self.infer_literals(right, None);
+            },
+            Expression::TupleIndex { left, .. } => self.infer_literals(left, None),
+            Expression::Slice { left, start, end } => {
+                self.infer_literals(left, None);
+
+                for bound in [start, end].into_iter().flatten() {
+                    self.infer_literals(bound, None);
+                }
+            },
+            Expression::Range { start, end, step, .. } => {
+                self.infer_literals(start, None);
+                self.infer_literals(end, None);
+
+                if let Some(step) = step {
+                    self.infer_literals(step, None);
+                }
+            },
+            Expression::FormatString(parts) => {
+                for part in parts {
+                    if let FormatPart::Expression(expression) = part {
+                        self.infer_literals(expression, None);
+                    }
+                }
+            },
+            _ => {}
+        }
+    }
+
+    // Names of variables that are known to be non-null when `condition` evaluates to `when`
+    fn non_null_names(&self, condition: &Expression, when: bool) -> Vec<String> {
+        match condition {
+            Expression::BinaryOp { left, operator, right } => {
+                match (operator, left.as_ref(), right.as_ref(), when) {
+                    (Operator::NotEqual, Expression::Identifier(name), Expression::NullLiteral, true)
+                    | (Operator::NotEqual, Expression::NullLiteral, Expression::Identifier(name), true)
+                    | (Operator::EqualEqual, Expression::Identifier(name), Expression::NullLiteral, false)
+                    | (Operator::EqualEqual, Expression::NullLiteral, Expression::Identifier(name), false) => vec![name.clone()],
+
+                    (Operator::And, _, _, true) | (Operator::Or, _, _, false) => {
+                        let mut names = self.non_null_names(left, when);
+                        names.extend(self.non_null_names(right, when));
+
+                        names
+                    },
+                    _ => Vec::new()
+                }
+            },
+            Expression::UnaryOp { expression, operator: Operator::Tilde } => self.non_null_names(expression, !when),
+            _ => Vec::new()
+        }
+    }
+
+    fn get_narrowed_data_type(&self, expression: &Expression, condition: &Expression, when: bool) -> io::Result<DataType> {
+        let mut previous: Vec<(String, Option<DataType>)> = Vec::new();
+
+        for name in self.non_null_names(condition, when) {
+            if let Some(inner) = self.narrowable(&name) {
+                let old = self.local.borrow_mut().narrow(name.clone(), inner);
+                previous.push((name, old));
+            }
+        }
+
+        let result = self.get_data_type(expression);
+
+        for (name, old) in previous.into_iter().rev() {
+            self.local.borrow_mut().restore_narrowing(name, old);
+        }
+
+        result
+    }
+
+    pub fn narrow_condition(&mut self, condition: &Expression, when: bool) {
+        for name in self.non_null_names(condition, when) {
+            self.narrow_variable(&name);
+        }
+    }
+
+    pub fn narrow_variable(&mut self, name: &str) {
+        if let Some(inner) = self.narrowable(name) {
+            self.local.borrow_mut().narrow(name.to_string(), inner);
+        }
+    }
+
+    // A variable that some function assigns can become null again during any call, so it is never narrowed
+    fn narrowable(&self, name: &str) -> Option<DataType> {
+        if self.local.borrow().is_captured(name) {
+            return None;
+        }
+
+        match self.get_variable(name) {
+            Ok(DataType::Nullable(inner)) => Some(*inner),
+            _ => None
+        }
+    }
+
+    pub fn forget_narrowing(&mut self, name: &str) {
+        self.local.borrow_mut().forget_narrowing(name);
+    }
+
+    // An assignment from inside a function also drops the narrowings of the enclosing blocks
+    pub fn assign_variable(&mut self, name: &str) {
+        self.local.borrow_mut().assign(name, false);
+        self.forget_narrowing(name);
+    }
+
+    // A pattern with a `kind` binds immutable names, loop variables over an immutable collection alias its elements
+    pub fn save_pattern(&mut self, pattern: &Pattern, data_type: DataType, kind: Option<&str>) -> io::Result<()> {
+        match (pattern, data_type) {
+            (Pattern::Identifier(name), data_type) => match kind {
+                Some(kind) => self.save_immutable(name.clone(), data_type, kind),
+                None => self.save_variable(name.clone(), data_type)
+            },
+            (Pattern::Wildcard, _) => Ok(()),
+            (Pattern::Tuple(patterns), DataType::Tuple(data_types)) if patterns.len() == data_types.len() => {
+                for (pattern, data_type) in patterns.iter().zip(data_types) {
+                    self.save_pattern(pattern, data_type, kind)?;
+                }
+
+                Ok(())
+            },
+            // The length of a list is only known at runtime
+            (Pattern::List(patterns), DataType::List(data_type)) => {
+                for pattern in patterns {
+                    self.save_pattern(pattern, (*data_type).clone(), kind)?;
+                }
+
+                Ok(())
+            },
+            (Pattern::Tuple(patterns) | Pattern::List(patterns), data_type) => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Cannot destructure {} into {} names!", data_type, patterns.len())
+            ))
+        }
+    }
+
+    pub fn save_variable(&mut self, name: String, data_type: DataType) -> io::Result<()> {
+        self.check_top_level(&name, false)?;
+        self.local.borrow_mut().declare(name, data_type)
+    }
+
+    pub fn global_save_variable(&mut self, name: String, data_type: DataType) -> io::Result<()> {
+        self.check_top_level(&name, true)?;
+        self.global.borrow_mut().declare(name, data_type)
+    }
+
+    // `kind` names the binding in errors, e.g. "constant" or "parameter"
+    pub fn save_immutable(&mut self, name: String, data_type: DataType, kind: &str) -> io::Result<()> {
+        self.check_top_level(&name, false)?;
+        self.local.borrow_mut().declare_immutable(name, data_type, kind)
+    }
+
+    pub fn global_save_immutable(&mut self, name: String, data_type: DataType, kind: &str) -> io::Result<()> {
+        self.check_top_level(&name, true)?;
+        self.global.borrow_mut().declare_immutable(name, data_type, kind)
+    }
+
+    // The top scope of a script and the global scope are one scope to the reader, `pub` only decides where a name is stored
+    fn check_top_level(&self, name: &str, public: bool) -> io::Result<()> {
+        let top_level = self.local.borrow().get_parent().is_some_and(|parent| Rc::ptr_eq(&parent, &self.global));
+
+        let declared = if public {
+            self.local.borrow().declares(name)
+        } else {
+            // Natives live in the global scope too, a script may still reuse their names
+            self.global.borrow().declares(name) && NativeRegistry::get().borrow().get_native(name).is_none()
+        };
+
+        if top_level && declared {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Variable {} is already declared in this scope!", name)
+            ));
         }
+
+        Ok(())
     }
 
-    pub fn save_variable(&mut self, name: String, data_type: DataType) {
-        self.local.borrow_mut().add(name, data_type);
+    pub fn shadows(&self, name: &str) -> bool {
+        self.local.borrow().shadows(name)
     }
 
-    pub fn global_save_variable(&mut self, name: String, data_type: DataType) {
-        self.global.borrow_mut().add(name, data_type);
+    // Rejects reassigning the variable as well as writing into its elements
+    pub fn check_mutable(&self, name: &str) -> io::Result<()> {
+        match self.local.borrow().get_immutable_kind(name) {
+            Some(kind) => Err(io::Error::new(io::ErrorKind::InvalidData, format!("Cannot assign to {} {}!", kind, name))),
+            None => Ok(())
+        }
+    }
+
+    // The immutable variable an expression reads from, writing through it would change that variable
+    pub fn immutable_root(&self, expression: &Expression) -> Option<(String, String)> {
+        match expression {
+            Expression::Identifier(name) => {
+                self.local.borrow().get_immutable_kind(name).map(|kind| (kind, name.clone()))
+            },
+            Expression::IdentifierIndex { left, .. }
+            | Expression::TupleIndex { left, .. } => self.immutable_root(left),
+            _ => None
+        }
     }
 }
\ No newline at end of file
@@ -1,6 +1,7 @@
 use super::data_type::DataType;
 use super::expression::Expression;
 use super::parameter::Parameter;
+use super::pattern::Pattern;
 
 
 #[derive(PartialEq, Debug, Clone)]
@@ -8,8 +9,16 @@ pub enum Statement {
     VariableDeclaration {
         name: String,
         public: bool,
+        constant: bool,
         data_type: Option<DataType>,
-        value: Option<Expression>
+        value: Option<Expression>,
+        doc: Option<String>
+    },
+    PatternDeclaration {
+        pattern: Pattern,
+        constant: bool,
+        data_type: Option<DataType>,
+        value: Expression
     },
     Assigment {
         name: String,
@@ -41,7 +50,7 @@ pub enum Statement {
         body: Vec<Statement>
     },
     ForLoopStatement {
-        name: String,
+        pattern: Pattern,
         list: Expression,
         body: Vec<Statement>
     },
@@ -53,14 +62,61 @@ pub enum Statement {
         public: bool,
         return_type: DataType,
         parameters: Vec<Parameter>,
-        body: Vec<Statement>
+        body: Vec<Statement>,
+        doc: Option<String>
     },
     EarlyReturn {
         name: String,
         body: Option<Vec<Statement>>
     },
+    TryStatement {
+        body: Vec<Statement>,
+        name: String,
+        catch_body: Vec<Statement>
+    },
+    Throw {
+        value: Expression
+    },
+    Yield {
+        value: Expression
+    },
+    // The body of a function with a `yield`, running it gives a generator instead of executing it
+    Generator {
+        body: Vec<Statement>,
+        data_type: DataType
+    },
     Use {
         file_name: String,
         body: Vec<Statement>
+    },
+    Impl {
+        data_type: DataType,
+        interface: Option<String>,
+        public: bool,
+        methods: Vec<Statement>
+    },
+    Interface {
+        name: String,
+        methods: Vec<(String, DataType)>
+    }
+}
+
+impl Statement {
+    // Whether the statement can suspend a generator, a nested function is a generator of its own
+    pub fn contains_yield(&self) -> bool {
+        match self {
+            Statement::Yield { .. } => true,
+            Statement::IfStatement { body, else_body, .. } => {
+                Statement::block_yields(body) || else_body.as_ref().is_some_and(|body| Statement::block_yields(body))
+            },
+            Statement::WhileStatement { body, .. } | Statement::ForLoopStatement { body, .. } => Statement::block_yields(body),
+            Statement::TryStatement { body, catch_body, .. } => Statement::block_yields(body) || Statement::block_yields(catch_body),
+            Statement::EarlyReturn { body, .. } => body.as_ref().is_some_and(|body| Statement::block_yields(body)),
+            _ => false
+        }
+    }
+
+    pub fn block_yields(body: &[Statement]) -> bool {
+        body.iter().any(Statement::contains_yield)
     }
 }
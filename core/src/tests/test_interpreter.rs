@@ -1,6 +1,8 @@
 use std::io;
+use std::rc::Rc;
 
 use crate::interpreter::interpreter::Interpreter;
+use crate::interpreter::range::Range;
 use crate::interpreter::value::ValueType;
 
 use crate::lexer::lexer::Lexer;
@@ -9,6 +11,27 @@ use crate::parser::operator::Operator;
 use crate::parser::parser::Parser;
 
 
+fn run_program(source: &str) -> Interpreter {
+    let mut lexer = Lexer::new(source.to_string());
+    lexer.lexer().unwrap();
+
+    let mut parser = Parser::new(lexer.get_tokens().clone(), lexer.get_token_pos().clone());
+    let statements = parser.parse_block_statement().unwrap();
+
+    let mut interpreter = Interpreter::empty();
+    interpreter.interpret_statements(statements).unwrap();
+
+    interpreter
+}
+
+fn get_string(interpreter: &Interpreter, name: &str) -> String {
+    let value = interpreter.get_variable(name).unwrap();
+    let local = interpreter.get_local();
+    let local = local.borrow();
+
+    local.value_to_string(&value).unwrap()
+}
+
 fn get_expression(expression_str: &str) -> Expression {
     let expression_str = expression_str.to_string();
     
@@ -179,3 +202,791 @@ fn test_interpreter_boolean_true_3() {
 // }
 
 
+
+#[test]
+fn test_interpreter_integer_64_bit() {
+    let expression = get_expression("3000000000 * 3");
+    let test_value = ValueType::Integer(9000000000);
+
+    let mut interpreter = Interpreter::empty();
+    let value = interpreter.interpret_expression(expression).unwrap().get_type().clone();
+
+    assert_eq!(value, test_value)
+}
+
+#[test]
+fn test_interpreter_integer_overflow() {
+    let expression = get_expression("9223372036854775807 + 1");
+
+    let mut interpreter = Interpreter::empty();
+    let err = interpreter.interpret_expression(expression).unwrap_err();
+
+    assert_eq!(err.to_string(), "Integer overflow: 9223372036854775807 + 1 does not fit into int!")
+}
+
+#[test]
+fn test_interpreter_format_string() {
+    let expression = get_expression(r#"f"sum: {2 + 3}, list: {[1, 2]}, tuple: {(1.5f, "a")}, nothing: {null}""#);
+    let test_value = ValueType::String(String::from("sum: 5, list: [1, 2], tuple: (1.5, a), nothing: null"));
+
+    let mut interpreter = Interpreter::empty();
+    let value = interpreter.interpret_expression(expression).unwrap().get_type().clone();
+
+    assert_eq!(value, test_value)
+}
+
+
+#[test]
+fn test_interpreter_map() {
+    let source = concat!(
+        "let ages: {string: int} = {\"kyryl\": 20, \"olha\": 22};\n",
+        "ages[\"kyryl\"] = 21;\n",
+        "let total = 0;\n",
+        "let names = \"\";\n",
+        "for (name, age) in ages {\n",
+        "    ages[name + \"!\"] = age;\n",
+        "    total += age;\n",
+        "    names += name;\n",
+        "}\n",
+        "let olha = ages[\"olha\"];\n"
+    );
+
+    let interpreter = run_program(source);
+
+    assert_eq!(get_string(&interpreter, "ages"), "{kyryl: 21, kyryl!: 21, olha: 22, olha!: 22}");
+    assert_eq!(get_string(&interpreter, "total"), "43");
+    assert_eq!(get_string(&interpreter, "names"), "kyrylolha");
+    assert_eq!(get_string(&interpreter, "olha"), "22");
+}
+
+#[test]
+fn test_interpreter_map_missing_key() {
+    let expression = get_expression(r#"{"a": 1}["b"]"#);
+
+    let mut interpreter = Interpreter::empty();
+    let err = interpreter.interpret_expression(expression).unwrap_err();
+
+    assert_eq!(err.to_string(), "Key \"b\" not found in map!")
+}
+
+#[test]
+fn test_interpreter_set_operations() {
+    let source = concat!(
+        "let a = {3, 1, 2, 1};\n",
+        "let b = {2, 3, 4};\n",
+        "let union = a | b;\n",
+        "let intersection = a & b;\n",
+        "let difference = a - b;\n",
+        "let precedence = a | b & {4} - {1};\n"
+    );
+
+    let interpreter = run_program(source);
+
+    assert_eq!(get_string(&interpreter, "a"), "{1, 2, 3}");
+    assert_eq!(get_string(&interpreter, "union"), "{1, 2, 3, 4}");
+    assert_eq!(get_string(&interpreter, "intersection"), "{2, 3}");
+    assert_eq!(get_string(&interpreter, "difference"), "{1}");
+    assert_eq!(get_string(&interpreter, "precedence"), "{1, 2, 3, 4}");
+}
+
+#[test]
+fn test_interpreter_in() {
+    let source = concat!(
+        "let in_list = 2 in [1, 2, 3];\n",
+        "let in_set = 5 in {1, 2, 3};\n",
+        "let in_map = \"a\" in {\"a\": 1};\n",
+        "let in_string = \"ell\" in \"Hello\";\n",
+        "let combined = 1 + 1 in [2] && !(\"x\" in \"abc\");\n"
+    );
+
+    let interpreter = run_program(source);
+
+    assert_eq!(get_string(&interpreter, "in_list"), "true");
+    assert_eq!(get_string(&interpreter, "in_set"), "false");
+    assert_eq!(get_string(&interpreter, "in_map"), "true");
+    assert_eq!(get_string(&interpreter, "in_string"), "true");
+    assert_eq!(get_string(&interpreter, "combined"), "true");
+}
+
+#[test]
+fn test_interpreter_null_operators() {
+    let source = concat!(
+        "let a: int? = null;\n",
+        "let b = a ?? 10;\n",
+        "a = 5;\n",
+        "let c = a ?? 10;\n",
+        "let pair: (int, string)? = null;\n",
+        "let first = pair?.0 ?? -1;\n",
+        "pair = (2, \"two\");\n",
+        "let second = pair?.1 ?? \"none\";\n",
+        "let list: [int?] = [1];\n",
+        "list[0] = null;\n",
+        "let set = a != null;\n"
+    );
+
+    let interpreter = run_program(source);
+
+    assert_eq!(get_string(&interpreter, "b"), "10");
+    assert_eq!(get_string(&interpreter, "c"), "5");
+    assert_eq!(get_string(&interpreter, "first"), "-1");
+    assert_eq!(get_string(&interpreter, "second"), "two");
+    assert_eq!(get_string(&interpreter, "list"), "[null]");
+    assert_eq!(get_string(&interpreter, "set"), "true");
+}
+
+
+#[test]
+fn test_interpreter_generic_functions() {
+    let source = concat!(
+        "function first<T>(xs: [T]): T {\n",
+        "    return xs[0];\n",
+        "}\n",
+        "function pair<A, B>(a: A, b: B): (A, B) {\n",
+        "    let both: (A, B) = (a, b);\n",
+        "    return both;\n",
+        "}\n",
+        "let a = first([3, 4]) + 1;\n",
+        "let b = first([\"x\", \"y\"]);\n",
+        "let c = pair(1, [true]);\n"
+    );
+
+    let interpreter = run_program(source);
+
+    assert_eq!(get_string(&interpreter, "a"), "4");
+    assert_eq!(get_string(&interpreter, "b"), "x");
+    assert_eq!(get_string(&interpreter, "c"), "(1, [true])");
+}
+
+
+#[test]
+fn test_interpreter_empty_literals() {
+    let source = concat!(
+        "function total(xs: [int]): int {\n",
+        "    let sum = 0;\n",
+        "    for x in xs {\n",
+        "        sum = sum + x;\n",
+        "    }\n",
+        "    return sum;\n",
+        "}\n",
+        "let xs: [int] = [];\n",
+        "let ages: {string: [int]} = {};\n",
+        "ages[\"a\"] = [];\n",
+        "let seen: {int} = {};\n",
+        "let sum = total([]);\n"
+    );
+
+    let interpreter = run_program(source);
+
+    assert_eq!(get_string(&interpreter, "xs"), "[]");
+    assert_eq!(get_string(&interpreter, "ages"), "{a: []}");
+    assert_eq!(get_string(&interpreter, "seen"), "{}");
+    assert_eq!(get_string(&interpreter, "sum"), "0");
+}
+
+
+#[test]
+fn test_interpreter_casts_and_conversions() {
+    let source = concat!(
+        "let truncated = -3.99 as int;\n",
+        "let half = 7 as float / 2;\n",
+        "let text = 12 as string + \"!\";\n",
+        "let parsed = int(\"42\") ?? 0;\n",
+        "let invalid = int(\"4x2\");\n",
+        "let fraction = float(\"1.5\") ?? 0.0;\n"
+    );
+
+    let interpreter = run_program(source);
+
+    assert_eq!(get_string(&interpreter, "truncated"), "-3");
+    assert_eq!(get_string(&interpreter, "half"), "3.5");
+    assert_eq!(get_string(&interpreter, "text"), "12!");
+    assert_eq!(get_string(&interpreter, "parsed"), "42");
+    assert_eq!(get_string(&interpreter, "invalid"), "null");
+    assert_eq!(get_string(&interpreter, "fraction"), "1.5");
+}
+
+#[test]
+fn test_interpreter_cast_out_of_range() {
+    let expression = get_expression("1e19 as int");
+
+    let mut interpreter = Interpreter::empty();
+    let err = interpreter.interpret_expression(expression).unwrap_err();
+
+    assert_eq!(err.to_string(), "Cannot cast 10000000000000000000 to int, it is out of range!");
+}
+
+#[test]
+fn test_interpreter_try_catch() {
+    let source = concat!(
+        "function check(a: int): int {\n",
+        "    if a == 0 {\n",
+        "        throw \"a is zero\";\n",
+        "    }\n",
+        "    return a;\n",
+        "}\n",
+        "let thrown = \"\";\n",
+        "let recovered = \"\";\n",
+        "let value = 0;\n",
+        "try {\n",
+        "    value = check(2);\n",
+        "    value = check(0);\n",
+        "    value = 10;\n",
+        "} catch error {\n",
+        "    thrown = error;\n",
+        "}\n",
+        "try {\n",
+        "    let big = 1e19 as int;\n",
+        "} catch error {\n",
+        "    recovered = error;\n",
+        "}\n",
+        "let after = value + 1;\n"
+    );
+
+    let interpreter = run_program(source);
+
+    assert_eq!(get_string(&interpreter, "thrown"), "a is zero");
+    assert_eq!(get_string(&interpreter, "recovered"), "Cannot cast 10000000000000000000 to int, it is out of range!");
+    assert_eq!(get_string(&interpreter, "value"), "2");
+    assert_eq!(get_string(&interpreter, "after"), "3");
+}
+
+#[test]
+fn test_interpreter_call_expression_callee() {
+    let source = concat!(
+        "function make_adder(n: int): function(int): int {\n",
+        "    return function(x: int): int {\n",
+        "        return x + n;\n",
+        "    };\n",
+        "}\n",
+        "let handlers = [make_adder(10), make_adder(20)];\n",
+        "let chained = make_adder(1)(2);\n",
+        "let indexed = handlers[1](5);\n",
+        "let literal = (function(x: int): int { return x * 2; })(3);\n"
+    );
+
+    let interpreter = run_program(source);
+
+    assert_eq!(get_string(&interpreter, "chained"), "3");
+    assert_eq!(get_string(&interpreter, "indexed"), "25");
+    assert_eq!(get_string(&interpreter, "literal"), "6");
+}
+
+#[test]
+fn test_interpreter_closure_counter() {
+    let source = concat!(
+        "function make_counter(): function(): int {\n",
+        "    let count = 0;\n",
+        "    return function(): int {\n",
+        "        count = count + 1;\n",
+        "        return count;\n",
+        "    };\n",
+        "}\n",
+        "let counter = make_counter();\n",
+        "let other = make_counter();\n",
+        "counter();\n",
+        "counter();\n",
+        "let third = counter();\n",
+        "let first = other();\n"
+    );
+
+    let interpreter = run_program(source);
+
+    assert_eq!(get_string(&interpreter, "third"), "3");
+    assert_eq!(get_string(&interpreter, "first"), "1");
+}
+
+#[test]
+fn test_interpreter_frees_scopes_with_functions() {
+    let source = concat!(
+        "function make_counter(): function(): int {\n",
+        "    let count = 0;\n",
+        "    function next(): int {\n",
+        "        count = count + 1;\n",
+        "        return count;\n",
+        "    }\n",
+        "    return next;\n",
+        "}\n",
+        "function double(x: int): int {\n",
+        "    return x * 2;\n",
+        "}\n",
+        "let twice = function(x: int): int { return double(double(x)); };\n",
+        "let counter = make_counter();\n",
+        "counter();\n",
+        "let second = counter();\n",
+        "let four = twice(1);\n"
+    );
+
+    let mut lexer = Lexer::new(source.to_string());
+    lexer.lexer().unwrap();
+
+    let mut parser = Parser::new(lexer.get_tokens().clone(), lexer.get_token_pos().clone());
+    let statements = parser.parse_block_statement().unwrap();
+
+    let mut interpreter = Interpreter::empty();
+    interpreter.enter_enviroment();
+    let scope = Rc::downgrade(&interpreter.get_local());
+
+    interpreter.interpret_statements(statements).unwrap();
+
+    // A returned function keeps the scope of its call alive
+    assert_eq!(get_string(&interpreter, "second"), "2");
+    assert_eq!(get_string(&interpreter, "four"), "4");
+
+    // Functions stored in the scope they captured must not keep it alive once it is left
+    interpreter.exit_enviroment().unwrap();
+    assert!(scope.upgrade().is_none());
+}
+
+#[test]
+fn test_interpreter_closure_callbacks() {
+    let source = concat!(
+        "let total = 0;\n",
+        "let handlers: {string: function(int)} = {};\n",
+        "function register(name: string, handler: function(int)) {\n",
+        "    handlers[name] = handler;\n",
+        "}\n",
+        "if true {\n",
+        "    let step = 5;\n",
+        "    register(\"add\", function(x: int) { total = total + x + step; });\n",
+        "    register(\"double\", function(x: int) { total = total * x; });\n",
+        "}\n",
+        "total = 1;\n",
+        "handlers[\"add\"](4);\n",
+        "handlers[\"double\"](2);\n"
+    );
+
+    let interpreter = run_program(source);
+
+    assert_eq!(get_string(&interpreter, "total"), "20");
+}
+
+#[test]
+fn test_interpreter_impl_methods() {
+    let source = concat!(
+        "impl int {\n",
+        "    function double(self): int {\n",
+        "        return self * 2;\n",
+        "    }\n",
+        "    function bump(mut self) {\n",
+        "        self = self + 1;\n",
+        "    }\n",
+        "}\n",
+        "impl [int] {\n",
+        "    function sum(self): int {\n",
+        "        let total = 0;\n",
+        "        for x in self {\n",
+        "            total = total + x;\n",
+        "        }\n",
+        "        return total;\n",
+        "    }\n",
+        "}\n",
+        "let a = 5;\n",
+        "let doubled = a.double().double();\n",
+        "a.bump();\n",
+        "let sum = [1, 2, 3].sum();\n"
+    );
+
+    let interpreter = run_program(source);
+
+    assert_eq!(get_string(&interpreter, "doubled"), "20");
+    assert_eq!(get_string(&interpreter, "a"), "6");
+    assert_eq!(get_string(&interpreter, "sum"), "6");
+}
+
+#[test]
+fn test_interpreter_interface_dispatch() {
+    let source = concat!(
+        "interface Shape {\n",
+        "    function area(self): int;\n",
+        "}\n",
+        "impl Shape for int {\n",
+        "    function area(self): int {\n",
+        "        return self * self;\n",
+        "    }\n",
+        "}\n",
+        "impl Shape for string {\n",
+        "    function area(self): int {\n",
+        "        return 100;\n",
+        "    }\n",
+        "}\n",
+        "let shapes: [Shape] = [3, \"square\"];\n",
+        "let total = 0;\n",
+        "for shape in shapes {\n",
+        "    total = total + shape.area();\n",
+        "}\n"
+    );
+
+    let interpreter = run_program(source);
+
+    assert_eq!(get_string(&interpreter, "total"), "109");
+}
+
+#[test]
+fn test_interpreter_builtin_interfaces() {
+    let source = concat!(
+        "impl Display for (int, int) {\n",
+        "    function to_string(self): string {\n",
+        "        return f\"{self.0}x{self.1}\";\n",
+        "    }\n",
+        "}\n",
+        "impl Equal for (int, int) {\n",
+        "    function equals(self, other: (int, int)): bool {\n",
+        "        return self.0 * self.1 == other.0 * other.1;\n",
+        "    }\n",
+        "}\n",
+        "let size = (2, 3);\n",
+        "let text = f\"size {size}\";\n",
+        "let cast = size as string;\n",
+        "let same = size == (3, 2);\n",
+        "let different = size != (1, 6);\n",
+        "let sizes = [size, (4, 5)];\n",
+        "let named = {\"a\": (size, 1)};\n",
+        "let nested = f\"{sizes} {named}\";\n",
+        "let same_items = [size, (4, 5)] == [(3, 2), (5, 4)];\n",
+        "let different_items = {\"a\": (size, 1)} != {\"a\": ((1, 6), 1)};\n",
+        "let plain_items = [[1, 2], [3]] == [[1, 2], [3]] && [1, 2] != [1, 2, 3];\n",
+        "let pair = (\"a\", 1);\n",
+        "let inside = \"\";\n",
+        "if true {\n",
+        "    impl Display for (string, int) {\n",
+        "        function to_string(self): string {\n",
+        "            return self.0;\n",
+        "        }\n",
+        "    }\n",
+        "    inside = f\"{pair}\";\n",
+        "}\n",
+        "let outside = f\"{pair}\";\n"
+    );
+
+    let interpreter = run_program(source);
+
+    assert_eq!(get_string(&interpreter, "nested"), "[2x3, 4x5] {a: (2x3, 1)}");
+    assert_eq!(get_string(&interpreter, "inside"), "a");
+    assert_eq!(get_string(&interpreter, "outside"), "(a, 1)");
+
+    assert_eq!(get_string(&interpreter, "text"), "size 2x3");
+    assert_eq!(get_string(&interpreter, "cast"), "2x3");
+    assert_eq!(get_string(&interpreter, "same"), "true");
+    assert_eq!(get_string(&interpreter, "different"), "false");
+    assert_eq!(get_string(&interpreter, "same_items"), "true");
+    assert_eq!(get_string(&interpreter, "different_items"), "false");
+    assert_eq!(get_string(&interpreter, "plain_items"), "true");
+}
+
+#[test]
+fn test_interpreter_operator_overloading() {
+    let source = concat!(
+        "impl (int, int) {\n",
+        "    function add(self, other: (int, int)): (int, int) {\n",
+        "        return (self.0 + other.0, self.1 + other.1);\n",
+        "    }\n",
+        "    function multiply(self, k: int): (int, int) {\n",
+        "        return (self.0 * k, self.1 * k);\n",
+        "    }\n",
+        "    function less(self, other: (int, int)): bool {\n",
+        "        return self.0 + self.1 < other.0 + other.1;\n",
+        "    }\n",
+        "    function index(self, i: int): int {\n",
+        "        if i == 0 {\n",
+        "            return self.0;\n",
+        "        }\n",
+        "        return self.1;\n",
+        "    }\n",
+        "}\n",
+        "let a = (1, 2);\n",
+        "let sum = a + (3, 4) * 2;\n",
+        "let greater = sum > a;\n",
+        "let at_most = sum <= a;\n",
+        "let second = sum[1];\n",
+        "let order = \"\";\n",
+        "function track(name: string, p: (int, int)): (int, int) {\n",
+        "    order += name;\n",
+        "    return p;\n",
+        "}\n",
+        "let tracked = track(\"a\", a) > track(\"b\", sum) || track(\"c\", a) <= track(\"d\", sum);\n",
+        "impl int {\n",
+        "    function less(self, other: (int, int)): bool {\n",
+        "        return self < other.0 + other.1;\n",
+        "    }\n",
+        "}\n",
+        "let mixed = sum > 16;\n",
+        "let total = a;\n",
+        "total += sum;\n"
+    );
+
+    let interpreter = run_program(source);
+
+    assert_eq!(get_string(&interpreter, "sum"), "(7, 10)");
+    assert_eq!(get_string(&interpreter, "order"), "abcd");
+    assert_eq!(get_string(&interpreter, "tracked"), "true");
+    assert_eq!(get_string(&interpreter, "mixed"), "true");
+    assert_eq!(get_string(&interpreter, "total"), "(8, 12)");
+    assert_eq!(get_string(&interpreter, "greater"), "true");
+    assert_eq!(get_string(&interpreter, "at_most"), "false");
+    assert_eq!(get_string(&interpreter, "second"), "10");
+}
+
+
+#[test]
+fn test_interpreter_destructuring() {
+    let source = concat!(
+        "let pair = (1, (\"two\", 3));\n",
+        "let (a, (b, _)) = pair;\n",
+        "a = 10;\n",
+        "let [x, y] = [[1, 2], [3, 4]];\n",
+        "let total = 0;\n",
+        "for [left, right] in [x, y] {\n",
+        "    total += left * right;\n",
+        "}\n"
+    );
+
+    let interpreter = run_program(source);
+
+    assert_eq!(get_string(&interpreter, "a"), "10");
+    assert_eq!(get_string(&interpreter, "pair"), "(1, (two, 3))");
+    assert_eq!(get_string(&interpreter, "b"), "two");
+    assert_eq!(get_string(&interpreter, "total"), "14");
+
+    let source = "let [a, b] = [1, 2, 3];\n";
+
+    let mut lexer = Lexer::new(source.to_string());
+    lexer.lexer().unwrap();
+
+    let mut parser = Parser::new(lexer.get_tokens().clone(), lexer.get_token_pos().clone());
+    let statements = parser.parse_block_statement().unwrap();
+
+    let mut interpreter = Interpreter::empty();
+    let error = interpreter.interpret_statements(statements).err().unwrap();
+
+    assert_eq!(error.to_string(), "Cannot destructure a list of 3 elements into 2 names!");
+}
+
+
+#[test]
+fn test_interpreter_ranges_and_iterators() {
+    let source = concat!(
+        "let up = \"\";\n",
+        "for i in 0..=10 step 5 {\n",
+        "    up += f\"{i} \";\n",
+        "}\n",
+        "let down = \"\";\n",
+        "for i in 10..0 step -3 {\n",
+        "    down += f\"{i} \";\n",
+        "}\n",
+        "let r = 1..9 step 2;\n",
+        "let has_five = 5 in r;\n",
+        "let has_six = 6 in r;\n",
+        "function countdown(from: int): function(): int? {\n",
+        "    let n = from;\n",
+        "    return function(): int? {\n",
+        "        if n == 0 {\n",
+        "            return null;\n",
+        "        }\n",
+        "        n -= 1;\n",
+        "        return n + 1;\n",
+        "    };\n",
+        "}\n",
+        "let counted = \"\";\n",
+        "for n in countdown(3) {\n",
+        "    counted += f\"{n}\";\n",
+        "}\n",
+        "function first_square_over(limit: int): int {\n",
+        "    for i in 0..1000000000 {\n",
+        "        if i * i > limit {\n",
+        "            return i;\n",
+        "        }\n",
+        "    }\n",
+        "    return 0;\n",
+        "}\n",
+        "let root = first_square_over(50);\n",
+        "let far = 9223372036854775806 in -9223372036854775807..9223372036854775807;\n"
+    );
+
+    let interpreter = run_program(source);
+
+    assert_eq!(get_string(&interpreter, "up"), "0 5 10 ");
+    assert_eq!(get_string(&interpreter, "down"), "10 7 4 1 ");
+    assert_eq!(get_string(&interpreter, "r"), "1..9 step 2");
+    assert_eq!(get_string(&interpreter, "has_five"), "true");
+    assert_eq!(get_string(&interpreter, "has_six"), "false");
+    assert_eq!(get_string(&interpreter, "counted"), "321");
+    assert_eq!(get_string(&interpreter, "root"), "8");
+    assert_eq!(get_string(&interpreter, "far"), "true");
+
+    assert_eq!(Range::new(-5, 5, 3, false).unwrap().len().unwrap(), 4);
+    assert_eq!(
+        Range::new(0, i64::MAX, 1, true).unwrap().len().unwrap_err().to_string(),
+        "Integer overflow: len(0..=9223372036854775807) does not fit into int!"
+    );
+}
+
+
+#[test]
+fn test_interpreter_generators() {
+    let source = concat!(
+        "function naturals(): function(): int? {\n",
+        "    let n = 0;\n",
+        "    while true {\n",
+        "        n += 1;\n",
+        "        yield n;\n",
+        "    }\n",
+        "}\n",
+        "function until(limit: int): string {\n",
+        "    let out = \"\";\n",
+        "    for n in naturals() {\n",
+        "        if n > limit {\n",
+        "            return out;\n",
+        "        }\n",
+        "        out += f\"{n}\";\n",
+        "    }\n",
+        "    return out;\n",
+        "}\n",
+        "let counted = until(4);\n",
+        "function steps(): function(): string? {\n",
+        "    try {\n",
+        "        yield \"try\";\n",
+        "        throw \"boom\";\n",
+        "    } catch e {\n",
+        "        yield e;\n",
+        "    }\n",
+        "    for i in 0..3 {\n",
+        "        if i == 1 {\n",
+        "            return;\n",
+        "        }\n",
+        "        yield f\"loop {i}\";\n",
+        "    }\n",
+        "    yield \"unreachable\";\n",
+        "}\n",
+        "let trace = \"\";\n",
+        "for step in steps() {\n",
+        "    trace += step + \";\";\n",
+        "}\n",
+        "let next = naturals();\n",
+        "let first = next();\n",
+        "let second = next();\n",
+        "let done = steps();\n",
+        "for step in done {\n",
+        "}\n",
+        "let after = done();\n"
+    );
+
+    let interpreter = run_program(source);
+
+    assert_eq!(get_string(&interpreter, "counted"), "1234");
+    assert_eq!(get_string(&interpreter, "trace"), "try;boom;loop 0;");
+    assert_eq!(get_string(&interpreter, "first"), "1");
+    assert_eq!(get_string(&interpreter, "second"), "2");
+    assert_eq!(get_string(&interpreter, "after"), "null");
+}
+
+
+#[test]
+fn test_interpreter_slices_and_negative_indices() {
+    let source = concat!(
+        "let xs = [1, 2, 3, 4, 5];\n",
+        "let middle = xs[1:3];\n",
+        "let init = xs[:-1];\n",
+        "let rest = xs[2:];\n",
+        "let last_two = xs[-2:];\n",
+        "let last = xs[-1];\n",
+        "middle[0] = 20;\n",
+        "xs[-1] = 50;\n",
+        "let word = \"hello\";\n",
+        "let part = word[1:3];\n",
+        "let end = word[-1];\n",
+        "word[-5] = \"j\";\n"
+    );
+
+    let interpreter = run_program(source);
+
+    assert_eq!(get_string(&interpreter, "middle"), "[20, 3]");
+    assert_eq!(get_string(&interpreter, "init"), "[1, 2, 3, 4]");
+    assert_eq!(get_string(&interpreter, "rest"), "[3, 4, 5]");
+    assert_eq!(get_string(&interpreter, "last_two"), "[4, 5]");
+    assert_eq!(get_string(&interpreter, "last"), "5");
+    assert_eq!(get_string(&interpreter, "xs"), "[1, 2, 3, 4, 50]");
+    assert_eq!(get_string(&interpreter, "part"), "el");
+    assert_eq!(get_string(&interpreter, "end"), "o");
+    assert_eq!(get_string(&interpreter, "word"), "jello");
+
+    for (source, message) in [
+        ("let xs = [1, 2];\nlet a = xs[2];\n", "Index 2 is out of range for a list of length 2!"),
+        ("let xs = [1, 2];\nxs[-3] = 0;\n", "Index -3 is out of range for a list of length 2!"),
+        ("let xs = [1, 2];\nlet a = xs[1:3];\n", "Slice [1:3] is out of range for a list of length 2!"),
+        ("let a = \"ab\"[2:1];\n", "Slice [2:1] is out of range for a string of length 2!")
+    ] {
+        let mut lexer = Lexer::new(source.to_string());
+        lexer.lexer().unwrap();
+
+        let mut parser = Parser::new(lexer.get_tokens().clone(), lexer.get_token_pos().clone());
+        let statements = parser.parse_block_statement().unwrap();
+
+        let mut interpreter = Interpreter::empty();
+        let error = interpreter.interpret_statements(statements).err().unwrap();
+
+        assert_eq!(error.to_string(), message);
+    }
+}
+
+
+#[test]
+fn test_interpreter_unicode_strings() {
+    let source = concat!(
+        "let word = \"héllo ✓\";\n",
+        "let second = word[1];\n",
+        "let last = word[-1];\n",
+        "let part = word[1:4];\n",
+        "let count = 0;\n",
+        "let reversed = \"\";\n",
+        "for c in word { count += 1; reversed = c + reversed; }\n",
+        "word[1] = \"ё\";\n"
+    );
+
+    let interpreter = run_program(source);
+
+    assert_eq!(get_string(&interpreter, "second"), "é");
+    assert_eq!(get_string(&interpreter, "last"), "✓");
+    assert_eq!(get_string(&interpreter, "part"), "éll");
+    assert_eq!(get_string(&interpreter, "count"), "7");
+    assert_eq!(get_string(&interpreter, "reversed"), "✓ olléh");
+    assert_eq!(get_string(&interpreter, "word"), "hёllo ✓");
+}
+
+
+#[test]
+fn test_interpreter_constants_through_aliases() {
+    let source = concat!(
+        "let numbers = [1, 2];\n",
+        "const frozen = numbers;\n",
+        "numbers[0] = 10;\n",
+        "const pairs = [[1], [2]];\n",
+        "let first = pairs[0][0];\n",
+        "first = 5;\n"
+    );
+
+    let interpreter = run_program(source);
+
+    assert_eq!(get_string(&interpreter, "numbers"), "[10, 2]");
+    assert_eq!(get_string(&interpreter, "frozen"), "[1, 2]");
+    assert_eq!(get_string(&interpreter, "pairs"), "[[1], [2]]");
+
+    for source in [
+        "const xs = [1];\nlet ys = xs;\nys[0] = 7;\n",
+        "const xs = [1];\nlet ys = xs;\nys = [3];\n",
+        "const xs = [1];\nfunction id(a: [int]): [int] {\n    return a;\n}\nlet ys = id(xs);\nys[0] = 8;\n",
+        "const xs = [1];\nlet (a, _) = (xs, 1);\na[0] = 4;\n",
+        "const xs = [1];\nfor y in [xs] {\n    y[0] = 5;\n}\n"
+    ] {
+        let mut lexer = Lexer::new(source.to_string());
+        lexer.lexer().unwrap();
+
+        let mut parser = Parser::new(lexer.get_tokens().clone(), lexer.get_token_pos().clone());
+        let statements = parser.parse_block_statement().unwrap();
+
+        let mut interpreter = Interpreter::empty();
+        let error = interpreter.interpret_statements(statements).err().unwrap();
+
+        assert_eq!(error.to_string(), "Cannot assign to constant xs!");
+        assert_eq!(get_string(&interpreter, "xs"), "[1]");
+    }
+}
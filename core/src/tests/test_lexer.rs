@@ -1,6 +1,6 @@
 use crate::*;
 use lexer::lexer::Lexer;
-use lexer::token::Token;
+use lexer::token::{FormatToken, Token};
 
 #[test]
 fn test_lexer_easy() {
@@ -96,4 +96,229 @@ fn test_lexer_identefier_underscore() {
     let tokens = lexer.get_tokens();
 
     assert_eq!(tokens, &expected_tokens);
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_lexer_integer_64_bit() {
+    let source = String::from("let big: int = 9223372036854775807;");
+
+    let mut lexer = Lexer::new(source);
+    lexer.lexer().unwrap();
+
+    let tokens = lexer.get_tokens();
+
+    assert_eq!(tokens[5], Token::IntegerLiteral(i64::MAX));
+}
+
+
+#[test]
+fn test_lexer_integer_out_of_range() {
+    let source = concat!(
+        "let a = 10;\n",
+        "let b = 9223372036854775808;\n"
+    );
+
+    let mut lexer = Lexer::new(source.to_string());
+    let err = lexer.lexer().unwrap_err();
+
+    assert_eq!(err.to_string(), "kyryl-script: At Main:2:28: Integer literal 9223372036854775808 is out of range for int (max 9223372036854775807)!");
+}
+
+
+#[test]
+fn test_lexer_string_escapes() {
+    let source = String::from(r#"print("line\n\ttab \"quoted\" \\ \u{1F600}");"#);
+
+    let mut lexer = Lexer::new(source);
+    lexer.lexer().unwrap();
+
+    let tokens = lexer.get_tokens();
+
+    assert_eq!(tokens[2], Token::StringLiteral(String::from("line\n\ttab \"quoted\" \\ \u{1F600}")));
+}
+
+
+#[test]
+fn test_lexer_raw_strings() {
+    let source = String::from(r###"let a = r"C:\path\n"; let b = r#"say "hi""#;"###);
+
+    let mut lexer = Lexer::new(source);
+    lexer.lexer().unwrap();
+
+    let tokens = lexer.get_tokens();
+
+    assert_eq!(tokens[3], Token::StringLiteral(String::from(r"C:\path\n")));
+    assert_eq!(tokens[8], Token::StringLiteral(String::from(r#"say "hi""#)));
+}
+
+
+#[test]
+fn test_lexer_multiline_string() {
+    let source = concat!(
+        "let a = \"\"\"first\n",
+        "second \"quoted\" text\"\"\";\n",
+        "let b = 1;\n"
+    );
+
+    let mut lexer = Lexer::new(source.to_string());
+    lexer.lexer().unwrap();
+
+    let tokens = lexer.get_tokens();
+    let token_pos = lexer.get_token_pos();
+
+    assert_eq!(tokens[3], Token::StringLiteral(String::from("first\nsecond \"quoted\" text")));
+    assert_eq!(*token_pos[3].get_line(), 0);
+    assert_eq!(*token_pos[5].get_line(), 2);
+}
+
+
+#[test]
+fn test_lexer_unterminated_string() {
+    let source = concat!(
+        "let a = \"first\n",
+        "second\";\n"
+    );
+
+    let mut lexer = Lexer::new(source.to_string());
+    let err = lexer.lexer().unwrap_err();
+
+    assert_eq!(err.to_string(), "kyryl-script: At Main:1:15: Unterminated string literal, use \"\"\" for multi-line strings!");
+}
+
+
+#[test]
+fn test_lexer_format_string() {
+    let source = String::from(r#"f"Hello {name}, {{you}} have {count + 1} items""#);
+
+    let mut lexer = Lexer::new(source);
+    lexer.lexer().unwrap();
+
+    let expected_tokens: Vec<Token> = vec![
+        Token::FormatString(vec![
+            FormatToken::Literal(String::from("Hello ")),
+            FormatToken::Expression(vec![Token::Identifier(String::from("name"))]),
+            FormatToken::Literal(String::from(", {you} have ")),
+            FormatToken::Expression(vec![Token::Identifier(String::from("count")), Token::Plus, Token::IntegerLiteral(1)]),
+            FormatToken::Literal(String::from(" items"))
+        ])
+    ];
+
+    assert_eq!(lexer.get_tokens(), &expected_tokens);
+}
+
+
+#[test]
+fn test_lexer_block_comments() {
+    let source = concat!(
+        "let a = 10 /* outer /* inner\n",
+        "*/ still comment */ / 2;// trailing\n",
+        "let b = a;\n"
+    );
+
+    let mut lexer = Lexer::new(source.to_string());
+    lexer.lexer().unwrap();
+
+    let expected_tokens: Vec<Token> = vec![
+        Token::Let,
+        Token::Identifier(String::from("a")),
+        Token::Equal,
+        Token::IntegerLiteral(10),
+        Token::Divide,
+        Token::IntegerLiteral(2),
+        Token::Semicolon,
+        Token::Let,
+        Token::Identifier(String::from("b")),
+        Token::Equal,
+        Token::Identifier(String::from("a")),
+        Token::Semicolon
+    ];
+
+    assert_eq!(lexer.get_tokens(), &expected_tokens);
+    assert_eq!(*lexer.get_token_pos()[7].get_line(), 2);
+}
+
+
+#[test]
+fn test_lexer_unterminated_block_comment() {
+    let source = concat!(
+        "let a = 10;\n",
+        "/* never /* closed */\n"
+    );
+
+    let mut lexer = Lexer::new(source.to_string());
+    let err = lexer.lexer().unwrap_err();
+
+    assert_eq!(err.to_string(), "kyryl-script: At Main:2:1: Unterminated block comment!");
+}
+
+
+#[test]
+fn test_lexer_number_literals() {
+    let source = "1_000 0xFF 0b1010 0o17 1.5 2.5f 1e-3 3f a.0.1 1..5 0..=2";
+
+    let mut lexer = Lexer::new(source.to_string());
+    lexer.lexer().unwrap();
+
+    let expected_tokens: Vec<Token> = vec![
+        Token::IntegerLiteral(1000),
+        Token::IntegerLiteral(255),
+        Token::IntegerLiteral(10),
+        Token::IntegerLiteral(15),
+        Token::FloatLiteral(1.5),
+        Token::FloatLiteral(2.5),
+        Token::FloatLiteral(0.001),
+        Token::FloatLiteral(3.0),
+        Token::Identifier(String::from("a")),
+        Token::Dot,
+        Token::IntegerLiteral(0),
+        Token::Dot,
+        Token::IntegerLiteral(1),
+        Token::IntegerLiteral(1),
+        Token::DotDot,
+        Token::IntegerLiteral(5),
+        Token::IntegerLiteral(0),
+        Token::DotDotEqual,
+        Token::IntegerLiteral(2)
+    ];
+
+    assert_eq!(lexer.get_tokens(), &expected_tokens);
+}
+
+
+#[test]
+fn test_lexer_invalid_number_literals() {
+    let mut lexer = Lexer::new(String::from("let a = 12abc;"));
+    let err = lexer.lexer().unwrap_err();
+    assert_eq!(err.to_string(), "kyryl-script: At Main:1:14: Invalid number literal 12abc!");
+
+    let mut lexer = Lexer::new(String::from("let a = 0x;"));
+    let err = lexer.lexer().unwrap_err();
+    assert_eq!(err.to_string(), "kyryl-script: At Main:1:11: Invalid integer literal 0x!");
+
+    let mut lexer = Lexer::new(String::from("let a = 0xFFFFFFFFFFFFFFFFF;"));
+    let err = lexer.lexer().unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "kyryl-script: At Main:1:28: Integer literal 0xFFFFFFFFFFFFFFFFF is out of range for int (max 9223372036854775807)!"
+    );
+}
+
+
+#[test]
+fn test_lexer_unexpected_character() {
+    let source = concat!(
+        "let a = 10;\n",
+        "let b = a @ 2;\n"
+    );
+
+    let mut lexer = Lexer::new(source.to_string());
+    let err = lexer.lexer().unwrap_err();
+
+    assert_eq!(err.to_string(), "kyryl-script: At Main:2:11: Unexpected character '@'!");
+
+    let mut lexer = Lexer::new(String::from("let a = 10;\n  a = f\"{a}\";\n"));
+    lexer.lexer().unwrap();
+
+    let columns: Vec<(i32, i32)> = lexer.get_token_pos().iter().map(|pos| (*pos.get_line(), *pos.get_column())).collect();
+    assert_eq!(columns, vec![(0, 0), (0, 4), (0, 6), (0, 8), (0, 10), (1, 2), (1, 4), (1, 6), (1, 12)]);
+}
@@ -7,7 +7,7 @@ use lexer::token::Token;
 use parser::parser::Parser;
 use parser::data_type::DataType;
 use parser::operator::Operator;
-use parser::expression::Expression;
+use parser::expression::{Expression, FormatPart};
 use parser::statement::Statement;
 
 #[test]
@@ -145,8 +145,10 @@ fn test_variable_declaration_statement() {
     let test_statement = Statement::VariableDeclaration {
         name: String::from("a"),
         public: false,
+        constant: false,
         data_type: Some(DataType::Int),
-        value: Some(Expression::IntegerLiteral(10))
+        value: Some(Expression::IntegerLiteral(10)),
+        doc: None
     };
 
     let mut parser = Parser::new(tokens, Vec::new());
@@ -260,8 +262,8 @@ fn test_function_call_statement() {
     let mut lexer = Lexer::new(source.to_string());
     lexer.lexer().unwrap();
 
-    let test_expression = Expression::FunctionCall(String::from("print"), vec![
-        Expression::FunctionCall(String::from("add"), vec![Expression::IntegerLiteral(20), Expression::IntegerLiteral(10)]),
+    let test_expression = Expression::FunctionCall(Box::new(Expression::Identifier(String::from("print"))), vec![
+        Expression::FunctionCall(Box::new(Expression::Identifier(String::from("add"))), vec![Expression::IntegerLiteral(20), Expression::IntegerLiteral(10)]),
         Expression::IntegerLiteral(20)
     ]);
 
@@ -396,7 +398,7 @@ fn test_parser_string_index_1() {
 
 #[test]
 fn test_parser_index_assingment_statment() {
-    let mut lexer = Lexer::new(String::from("some_list[10][20] = 20;"));
+    let mut lexer = Lexer::new(String::from("let some_list = [[1]];\nsome_list[10][20] = 20;"));
     lexer.lexer().unwrap();
 
     let test_statement = Statement::AssigmentIndex { 
@@ -406,6 +408,7 @@ fn test_parser_index_assingment_statment() {
     };
 
     let mut parser = Parser::new(lexer.get_tokens().clone(), Vec::new());
+    parser.parse_statement().unwrap();
     let statement = parser.parse_statement().unwrap().unwrap();
 
     assert_eq!(statement, test_statement);
@@ -434,8 +437,10 @@ fn test_parser_callback() {
     let test_statement = Statement::VariableDeclaration { 
         name: String::from("test"),
         public: false,
+        constant: false,
         data_type: None, 
-        value:  Some(Expression::FunctionLiteral { parameters: Vec::new(), return_type: DataType::void(), block: Vec::new() })
+        value:  Some(Expression::FunctionLiteral { parameters: Vec::new(), return_type: DataType::void(), block: Vec::new() }),
+        doc: None
     };
 
     let mut lexer = Lexer::new(source.to_string());
@@ -458,10 +463,12 @@ fn test_parser_callback_with_type() {
     let test_statement = Statement::VariableDeclaration { 
         name: String::from("number_32"), 
         public: false,
+        constant: false,
         data_type: None, 
         value:  Some(Expression::FunctionLiteral { parameters: Vec::new(), return_type: DataType::Int, block: vec![
             Statement::ReturnStatement { value: Some(Expression::IntegerLiteral(32)) }
-        ] })
+        ] }),
+        doc: None
     };
 
     let mut lexer = Lexer::new(source.to_string());
@@ -485,8 +492,9 @@ fn test_parser_callback_with_parameters_and_type() {
     let test_statement = Statement::VariableDeclaration { 
         name: String::from("sum"), 
         public: false,
+        constant: false,
         data_type: None, 
-        value:  Some(Expression::FunctionLiteral { parameters: vec![Parameter {name: String::from("a"), data_type: DataType::Int}, Parameter {name: String::from("b"), data_type: DataType::Int}], return_type: DataType::Int, block: vec![
+        value:  Some(Expression::FunctionLiteral { parameters: vec![Parameter {name: String::from("a"), data_type: DataType::Int, mutable: false}, Parameter {name: String::from("b"), data_type: DataType::Int, mutable: false}], return_type: DataType::Int, block: vec![
             Statement::ReturnStatement { 
                 value: Some(Expression::BinaryOp { 
                     left: Box::new(Expression::Identifier(String::from("a"))), 
@@ -494,7 +502,8 @@ fn test_parser_callback_with_parameters_and_type() {
                     right: Box::new(Expression::Identifier(String::from("b"))) 
                 }) 
             }
-        ]})
+        ]}),
+        doc: None
     };
 
     let mut lexer = Lexer::new(source.to_string());
@@ -505,3 +514,178 @@ fn test_parser_callback_with_parameters_and_type() {
 
     assert_eq!(statement, test_statement); 
 }
+
+#[test]
+fn test_parser_format_string() {
+    let mut lexer = Lexer::new(String::from(r#"f"{a + 1} and {b}""#));
+    lexer.lexer().unwrap();
+
+    let test_expression = Expression::FormatString(vec![
+        FormatPart::Expression(Expression::BinaryOp {
+            left: Box::new(Expression::Identifier(String::from("a"))),
+            operator: Operator::Plus,
+            right: Box::new(Expression::IntegerLiteral(1))
+        }),
+        FormatPart::Literal(String::from(" and ")),
+        FormatPart::Expression(Expression::Identifier(String::from("b")))
+    ]);
+
+    let mut parser = Parser::new(lexer.get_tokens().clone(), Vec::new());
+    let expression = parser.parse_expression().unwrap();
+
+    assert_eq!(expression, test_expression);
+}
+
+#[test]
+fn test_parser_doc_comments() {
+    let source = concat!(
+        "/// Adds two numbers.\n",
+        "/// Returns their sum.\n",
+        "function add(a: int, b: int): int {\n",
+        "    /// Result of the addition\n",
+        "    let result = a + b;\n",
+        "    return result;\n",
+        "    /// Nothing to document here\n",
+        "}\n",
+    );
+
+    let mut lexer = Lexer::new(source.to_string());
+    lexer.lexer().unwrap();
+
+    let mut parser = Parser::new(lexer.get_tokens().clone(), lexer.get_token_pos().clone());
+    let statements = parser.parse_block_statement().unwrap();
+
+    if let Statement::Function { doc, body, .. } = &statements[0] {
+        assert_eq!(doc, &Some(String::from("Adds two numbers.\nReturns their sum.")));
+
+        if let Statement::VariableDeclaration { doc, .. } = &body[0] {
+            assert_eq!(doc, &Some(String::from("Result of the addition")));
+        } else {
+            panic!("Expected variable declaration!");
+        }
+    } else {
+        panic!("Expected function declaration!");
+    }
+}
+
+#[test]
+fn test_parser_map() {
+    let mut lexer = Lexer::new(String::from("let a: {string: int} = {\"a\": 1, \"b\": 2};"));
+    lexer.lexer().unwrap();
+
+    let test_statement = Statement::VariableDeclaration {
+        name: String::from("a"),
+        public: false,
+        constant: false,
+        data_type: Some(DataType::Map(Box::new(DataType::String), Box::new(DataType::Int))),
+        value: Some(Expression::MapLiteral(vec![
+            (Expression::StringLiteral(String::from("a")), Expression::IntegerLiteral(1)),
+            (Expression::StringLiteral(String::from("b")), Expression::IntegerLiteral(2))
+        ])),
+        doc: None
+    };
+
+    let mut parser = Parser::new(lexer.get_tokens().clone(), lexer.get_token_pos().clone());
+    let statement = parser.parse_statement().unwrap().unwrap();
+
+    assert_eq!(statement, test_statement);
+}
+
+#[test]
+fn test_parser_range() {
+    let mut lexer = Lexer::new(String::from("let a: range = 1 + 1..=10 step 2;"));
+    lexer.lexer().unwrap();
+
+    let test_statement = Statement::VariableDeclaration {
+        name: String::from("a"),
+        public: false,
+        constant: false,
+        data_type: Some(DataType::Range),
+        value: Some(Expression::Range {
+            start: Box::new(Expression::BinaryOp {
+                left: Box::new(Expression::IntegerLiteral(1)),
+                operator: Operator::Plus,
+                right: Box::new(Expression::IntegerLiteral(1))
+            }),
+            end: Box::new(Expression::IntegerLiteral(10)),
+            step: Some(Box::new(Expression::IntegerLiteral(2))),
+            inclusive: true
+        }),
+        doc: None
+    };
+
+    let mut parser = Parser::new(lexer.get_tokens().clone(), lexer.get_token_pos().clone());
+    let statement = parser.parse_statement().unwrap().unwrap();
+
+    assert_eq!(statement, test_statement);
+}
+
+#[test]
+fn test_parser_slice() {
+    let mut lexer = Lexer::new(String::from("let a = [1, 2, 3];\nlet b = a[1:-1][:2];"));
+    lexer.lexer().unwrap();
+
+    let test_statement = Statement::VariableDeclaration {
+        name: String::from("b"),
+        public: false,
+        constant: false,
+        data_type: None,
+        value: Some(Expression::Slice {
+            left: Box::new(Expression::Slice {
+                left: Box::new(Expression::Identifier(String::from("a"))),
+                start: Some(Box::new(Expression::IntegerLiteral(1))),
+                end: Some(Box::new(Expression::UnaryOp {
+                    expression: Box::new(Expression::IntegerLiteral(1)),
+                    operator: Operator::Minus
+                }))
+            }),
+            start: None,
+            end: Some(Box::new(Expression::IntegerLiteral(2)))
+        }),
+        doc: None
+    };
+
+    let mut parser = Parser::new(lexer.get_tokens().clone(), lexer.get_token_pos().clone());
+    parser.parse_statement().unwrap();
+    let statement = parser.parse_statement();
+
+    assert_eq!(statement.unwrap().unwrap(), test_statement);
+}
+
+#[test]
+fn test_parser_swapped_comparison() {
+    let source = concat!(
+        "impl (int, int) {\n",
+        "    function less(self, other: (int, int)): bool {\n",
+        "        return self.0 < other.0;\n",
+        "    }\n",
+        "}\n",
+        "let a = (1, 2);\n",
+        "let b = a <= (3, 4);"
+    );
+    let mut lexer = Lexer::new(String::from(source));
+    lexer.lexer().unwrap();
+
+    let test_statement = Statement::VariableDeclaration {
+        name: String::from("b"),
+        public: false,
+        constant: false,
+        data_type: None,
+        value: Some(Expression::UnaryOp {
+            expression: Box::new(Expression::SwappedCall {
+                method: String::from("(int, int).less"),
+                left: Box::new(Expression::Identifier(String::from("a"))),
+                right: Box::new(Expression::TupleLiteral(vec![Expression::IntegerLiteral(3), Expression::IntegerLiteral(4)]))
+            }),
+            operator: Operator::Tilde
+        }),
+        doc: None
+    };
+
+    let mut parser = Parser::new(lexer.get_tokens().clone(), lexer.get_token_pos().clone());
+    parser.parse_statement().unwrap();
+    parser.parse_statement().unwrap();
+    let statement = parser.parse_statement();
+
+    assert_eq!(statement.unwrap().unwrap(), test_statement);
+}
@@ -1,5 +1,11 @@
+use std::io;
+
+use crate::interpreter::value::{Value, ValueType};
 use crate::lexer::lexer::Lexer;
+use crate::native_registry::native_function::NativeFunction;
+use crate::parser::data_type::DataType;
 use crate::parser::parser::Parser;
+use crate::parser::semantic_analyzer::SemanticAnalyzer;
 
 
 #[test]
@@ -34,6 +40,7 @@ fn test_function_enviroment_parameters() {
     let source = concat!(
         "function foo(bar: int): int {\n",
         "    let a: int = bar;\n",
+        "    return a;\n",
         "}\n",
     );
 
@@ -66,6 +73,7 @@ fn test_function_enviroment_parameters_error() {
 fn test_function_enviroment_parameters_out_of_function() {
     let source = concat!(
         "function foo(bar: float): int {\n",
+        "    return 0;\n",
         "}\n",
         "let a: int = bar;"
     );
@@ -167,7 +175,7 @@ fn test_function_enviroment_expression_mismatch() {
 #[test]
 fn test_function_enviroment_null_error() {
     let source = concat!(
-        "let a: int = null;\n",
+        "let a: int? = null;\n",
         "let b = a + 10;"
     );
 
@@ -184,7 +192,7 @@ fn test_function_enviroment_null_error() {
 #[test]
 fn test_function_assigment_error() {
     let source = concat!(
-        "let a: int = null;\n",
+        "let a: int? = null;\n",
         "a = \"Hello!\";\n"
     );
 
@@ -212,3 +220,736 @@ fn test_function_tuple_index() {
     assert_eq!(parser.parse_block_statement().unwrap_err().to_string(), "Different data types in expression and actual data type.")
 }
 
+
+
+#[test]
+fn test_map_key_type() {
+    let source = concat!(
+        "let ages: {string: int} = {\"kyryl\": 20, \"olha\": 22};\n",
+        "let age: int = ages[\"kyryl\"];\n",
+        "for (name, value) in ages {\n",
+        "    let n: string = name;\n",
+        "    let v: int = value;\n",
+        "}\n",
+        "let wrong: int = ages[1];\n"
+    );
+
+    let mut lexer = Lexer::new(source.to_string());
+    lexer.lexer().unwrap();
+
+    let mut parser = Parser::new(lexer.get_tokens().clone(), lexer.get_token_pos().clone());
+
+    assert_eq!(parser.parse_block_statement().unwrap_err().to_string(), "Map key type mismatch, expected string got int!")
+}
+
+
+#[test]
+fn test_map_literal_types() {
+    let source = String::from("let a = {1: \"one\", \"two\": \"two\"};");
+
+    let mut lexer = Lexer::new(source);
+    lexer.lexer().unwrap();
+
+    let mut parser = Parser::new(lexer.get_tokens().clone(), lexer.get_token_pos().clone());
+    assert_eq!(parser.parse_block_statement().unwrap_err().to_string(), "Keys has different types in map!");
+
+    let source = String::from("let a = {[1]: 1};");
+
+    let mut lexer = Lexer::new(source);
+    lexer.lexer().unwrap();
+
+    let mut parser = Parser::new(lexer.get_tokens().clone(), lexer.get_token_pos().clone());
+    assert_eq!(parser.parse_block_statement().unwrap_err().to_string(), "Map key has to be int, string or boolean, got [int]!");
+}
+
+
+#[test]
+fn test_set_types() {
+    let source = concat!(
+        "let seen: {string} = {\"a\", \"b\"};\n",
+        "let has: bool = \"a\" in seen;\n",
+        "let wrong = 1 in seen;\n"
+    );
+
+    let mut lexer = Lexer::new(source.to_string());
+    lexer.lexer().unwrap();
+
+    let mut parser = Parser::new(lexer.get_tokens().clone(), lexer.get_token_pos().clone());
+    assert_eq!(parser.parse_block_statement().unwrap_err().to_string(), "Cannot check if int is in {string}!");
+
+    let source = String::from("let a = {1, 2} | {\"a\"};");
+
+    let mut lexer = Lexer::new(source);
+    lexer.lexer().unwrap();
+
+    let mut parser = Parser::new(lexer.get_tokens().clone(), lexer.get_token_pos().clone());
+    assert_eq!(parser.parse_block_statement().unwrap_err().to_string(), "Set type error!");
+}
+
+
+fn parse_error(source: &str) -> String {
+    let mut lexer = Lexer::new(source.to_string());
+    lexer.lexer().unwrap();
+
+    let mut parser = Parser::new(lexer.get_tokens().clone(), lexer.get_token_pos().clone());
+    parser.parse_block_statement().unwrap_err().to_string()
+}
+
+fn parse_warnings(source: &str) -> Vec<String> {
+    let mut lexer = Lexer::new(source.to_string());
+    lexer.lexer().unwrap();
+
+    let mut parser = Parser::new(lexer.get_tokens().clone(), lexer.get_token_pos().clone());
+    parser.parse_block_statement().unwrap();
+
+    parser.get_warnings().clone()
+}
+
+fn parse_ok(source: &str) {
+    let mut lexer = Lexer::new(source.to_string());
+    lexer.lexer().unwrap();
+
+    let mut parser = Parser::new(lexer.get_tokens().clone(), lexer.get_token_pos().clone());
+    parser.parse_block_statement().unwrap();
+}
+
+
+#[test]
+fn test_null_requires_nullable_type() {
+    assert_eq!(parse_error("let a: int = null;"), "Cannot assign null to int, declare it as int? to allow null!");
+    assert_eq!(parse_error("let a = null;"), "Cannot infer the type of null, annotate the variable with a nullable type!");
+    assert_eq!(parse_error("function foo(a: int) {\n}\nfoo(null);"), "Function signature mismatch");
+
+    parse_ok("let a: int? = null;\na = 10;\na = null;\nlet b: [int?] = [1, null];");
+}
+
+
+#[test]
+fn test_null_narrowing() {
+    parse_ok(concat!(
+        "function first(a: int?, b: int?): int {\n",
+        "    if a != null && a > 0 {\n",
+        "        return a + 1;\n",
+        "    }\n",
+        "    if b == null {\n",
+        "        return 0;\n",
+        "    }\n",
+        "    return b * 2;\n",
+        "}\n",
+        "function second(a: int?): int? {\n",
+        "    a?;\n",
+        "    return a;\n",
+        "}\n",
+        "function third(mut a: int?): int {\n",
+        "    while a == null {\n",
+        "        a = 1;\n",
+        "    }\n",
+        "    return a;\n",
+        "}\n"
+    ));
+
+    assert_eq!(
+        parse_error("function foo(a: int?): int {\n    if a != null {\n    }\n    return a + 1;\n}\n"),
+        "Attempt to perform an operation with a null value"
+    );
+    assert_eq!(
+        parse_error("let a: int? = 1;\nif a != null {\n    a = null;\n    let b = a + 1;\n}\n"),
+        "Attempt to perform an operation with a null value"
+    );
+
+    // A function that assigns a captured variable can make it null again during any call
+    assert_eq!(
+        parse_error(concat!(
+            "let a: int? = 5;\n",
+            "function clear() {\n",
+            "    a = null;\n",
+            "}\n",
+            "if a != null {\n",
+            "    clear();\n",
+            "    let b = a + 1;\n",
+            "}\n"
+        )),
+        "Attempt to perform an operation with a null value"
+    );
+    assert_eq!(
+        parse_error(concat!(
+            "let a: int? = 5;\n",
+            "if a != null {\n",
+            "    let clear = function() {\n",
+            "        a = null;\n",
+            "    };\n",
+            "    clear();\n",
+            "    let b = a + 1;\n",
+            "}\n"
+        )),
+        "Attempt to perform an operation with a null value"
+    );
+}
+
+
+#[test]
+fn test_early_return_checks() {
+    parse_ok(concat!(
+        "function first(a: int?): int {\n",
+        "    a? { return 0; };\n",
+        "    return a;\n",
+        "}\n",
+        "function second(a: int?) {\n",
+        "    a? { let b = 1; };\n",
+        "}\n"
+    ));
+
+    for (source, message) in [
+        ("function f(a: int?): int {\n    a?;\n    return a;\n}\n", "Early return on a must return a value of type int!"),
+        ("function f(a: int?): int {\n    a? { let b = 1; };\n    return a;\n}\n", "Early return on a must return a value of type int!"),
+        ("function f(a: int): int {\n    a?;\n    return a;\n}\n", "Cannot early return on a, int is not nullable!"),
+        ("let a: int? = null;\na?;\n", "No function context for early return!")
+    ] {
+        assert_eq!(parse_error(source), message);
+    }
+}
+
+
+#[test]
+fn test_null_operators() {
+    parse_ok(concat!(
+        "let a: int? = null;\n",
+        "let b: int = a ?? 10;\n",
+        "let pair: (int, string)? = null;\n",
+        "let name: string? = pair?.1;\n",
+        "let length: string = pair?.1 ?? \"none\";\n"
+    ));
+
+    assert_eq!(parse_error("let a: int? = null;\nlet b = a ?? \"x\";"), "Null coalescing type mismatch, expected int got string!");
+    assert_eq!(parse_error("let pair: (int, string)? = null;\nlet name = pair.1;"), "Attempt to perform an operation with a null value");
+}
+
+
+#[test]
+fn test_generic_functions() {
+    parse_ok(concat!(
+        "function first<T>(xs: [T]): T {\n",
+        "    return xs[0];\n",
+        "}\n",
+        "function apply<T, U>(x: T, f: function(T): U): U {\n",
+        "    let y: T = x;\n",
+        "    return f(first([y]));\n",
+        "}\n",
+        "let a: int = first([1, 2]) + 1;\n",
+        "let b: string = first([\"a\"]);\n",
+        "let c: string = apply(1, function(x: int): string { return \"one\"; });\n",
+        "let d: int? = first([null, 1]);\n"
+    ));
+
+    assert_eq!(
+        parse_error("function same<T>(a: T, b: T): T {\n    return a;\n}\nsame(1, \"a\");\n"),
+        "Cannot call same: Type parameter T cannot be both int and string!"
+    );
+    assert_eq!(
+        parse_error("function first<T>(xs: [T]): T {\n    return xs[0];\n}\nfirst(1);\n"),
+        "Cannot call first: Expected [T] got int!"
+    );
+    assert_eq!(
+        parse_error("function first<T>(xs: [T]): T {\n    return xs[0];\n}\nlet a: string = first([1]);\n"),
+        "Different data types in expression and actual data type."
+    );
+    assert_eq!(
+        parse_error("function apply<T>(x: T, f: function(T): int): int {\n    return f(1);\n}\n"),
+        "Cannot call f: Expected T got int!"
+    );
+    assert_eq!(parse_error("function make<T>(): int {\n    return 1;\n}\n"), "Type parameter T is not used by the parameters of make!");
+    assert_eq!(parse_error("function add<T>(a: T): T {\n    return a + 1;\n}\n"), "Arithmetic type error!");
+    assert_eq!(parse_error("let a: T = 1;"), "Unknown type T!");
+}
+
+
+#[test]
+fn test_empty_literals_take_expected_type() {
+    parse_ok(concat!(
+        "function total(xs: [int]): int {\n",
+        "    return xs[0];\n",
+        "}\n",
+        "function names(): [string] {\n",
+        "    return [];\n",
+        "}\n",
+        "let xs: [int] = [];\n",
+        "let nested: [[int]] = [[], [1]];\n",
+        "let ages: {string: int} = {};\n",
+        "let seen: {int} = {};\n",
+        "let pair: ([int], int) = ([], 1);\n",
+        "let maybe: [int]? = null;\n",
+        "let fallback: [int] = maybe ?? [];\n",
+        "xs = [];\n",
+        "nested[0] = [];\n",
+        "total([]);\n"
+    ));
+
+    assert_eq!(parse_error("let xs = [];"), "Cannot infer the type of an empty list, annotate it!");
+    assert_eq!(parse_error("let xs = {};"), "Cannot infer the type of an empty map or set, annotate it!");
+    assert_eq!(parse_error("let xs: {int} = [];"), "Cannot infer the type of an empty list, annotate it!");
+}
+
+
+#[test]
+fn test_statement_checks() {
+    parse_ok(concat!(
+        "function fib(n: int): int {\n",
+        "    if n <= 1 {\n",
+        "        return n;\n",
+        "    } else {\n",
+        "        return fib(n - 1) + fib(n - 2);\n",
+        "    }\n",
+        "}\n",
+        "let xs = [1, 2];\n",
+        "xs[0] = 3;\n",
+        "let total = 1.5;\n",
+        "total += 1;\n"
+    ));
+
+    assert_eq!(
+        parse_error("function add(a: int, b: int): int {\n    return a + b;\n}\nadd(1);\n"),
+        "Function add expects 2 arguments, got 1!"
+    );
+    assert_eq!(
+        parse_error("function sign(a: int): int {\n    if a > 0 {\n        return 1;\n    }\n}\n"),
+        "Function sign does not return a value on every path!"
+    );
+    assert_eq!(parse_error("let f = function(): int {\n};\n"), "Function literal does not return a value on every path!");
+    assert_eq!(parse_error("let xs = [1, 2];\nxs[0] = \"a\";\n"), "Assigment value mismatch!");
+    assert_eq!(parse_error("let a = 1;\na += 1.5;\n"), "Assigment value mismatch!");
+    assert_eq!(parse_error("let a = \"a\";\na -= 1;\n"), "Arithmetic type error!");
+    assert_eq!(parse_error("while 1 {\n}\n"), "While statment condition mismatch data_type, expected bool!");
+}
+
+
+#[test]
+fn test_block_scoping() {
+    parse_ok(concat!(
+        "let a = 1;\n",
+        "if a > 0 {\n",
+        "    let a = \"shadow\";\n",
+        "    let b: string = a;\n",
+        "}\n",
+        "let b: int = a;\n",
+        "for (key, value) in {\"x\": 1} {\n",
+        "    if value > 0 {\n",
+        "        let key = value;\n",
+        "    }\n",
+        "}\n",
+        "function len(xs: [int]): int {\n",
+        "    return 0;\n",
+        "}\n"
+    ));
+
+    assert_eq!(parse_error("let a = 1;\nlet a = 2;\n"), "Variable a is already declared in this scope!");
+    assert_eq!(parse_error("function foo(a: int, a: int) {\n}\n"), "Variable a is already declared in this scope!");
+    assert_eq!(parse_error("function foo(a: int) {\n    let a = 2;\n}\n"), "Variable a is already declared in this scope!");
+    assert_eq!(parse_error("for (a, a) in {1: 2} {\n}\n"), "Variable a is already declared in this scope!");
+    assert_eq!(parse_error("for a in [1] {\n    let a = 2;\n}\n"), "Variable a is already declared in this scope!");
+    assert_eq!(parse_error("if true {\n    let a = 1;\n}\nlet b = a;\n"), "Variable a not found!");
+    assert_eq!(parse_error("while false {\n    let a = 1;\n}\nlet b = a + 1;\n"), "Variable a not found!");
+    assert_eq!(
+        parse_error("function foo(a: int?): int? {\n    a? {\n        let b = 1;\n    };\n    return b;\n}\n"),
+        "Variable b not found!"
+    );
+    assert_eq!(parse_error("pub let a = 1;\nlet a = 2;\n"), "Variable a is already declared in this scope!");
+    assert_eq!(parse_error("const a = 1;\npub const a = 2;\n"), "Variable a is already declared in this scope!");
+}
+
+
+#[test]
+fn test_shadowing_warnings() {
+    let warnings = parse_warnings(concat!(
+        "let a = 1;\n",
+        "if a > 0 {\n",
+        "    const a = 2;\n",
+        "}\n",
+        "function foo(b: int) {\n",
+        "    for (b, _) in [(1, 2)] {\n",
+        "    }\n",
+        "}\n",
+        "let c = 3;\n"
+    ));
+
+    assert_eq!(warnings, vec![
+        "kyryl-script: At Main:3:16: warning: Variable a shadows a variable of an outer scope!",
+        "kyryl-script: At Main:6:28: warning: Variable b shadows a variable of an outer scope!"
+    ]);
+}
+
+
+#[test]
+fn test_immutable_bindings() {
+    parse_ok(concat!(
+        "const limit = 10;\n",
+        "let total = limit;\n",
+        "total += 1;\n",
+        "function reset(mut numbers: [int]) {\n",
+        "    numbers[0] = 0;\n",
+        "    numbers = [1];\n",
+        "}\n",
+        "let values = [1, 2];\n",
+        "reset(values);\n",
+        "for value in values {\n",
+        "    value = 3;\n",
+        "}\n"
+    ));
+
+    assert_eq!(parse_error("const a = 1;\na = 2;\n"), "Cannot assign to constant a!");
+    assert_eq!(parse_error("const a = 1;\na += 2;\n"), "Cannot assign to constant a!");
+    assert_eq!(parse_error("const xs = [1];\nxs[0] = 2;\n"), "Cannot assign to constant xs!");
+    assert_eq!(parse_error("function foo(xs: [int]) {\n    xs[0] = 2;\n}\n"), "Cannot assign to parameter xs!");
+    assert_eq!(parse_error("function foo(a: int) {\n    a = 2;\n}\n"), "Cannot assign to parameter a!");
+    assert_eq!(parse_error("function foo() {\n}\nfoo = function() {};\n"), "Cannot assign to function foo!");
+    assert_eq!(
+        parse_error("function reset(mut xs: [[int]]) {\n}\nconst xs = [[1]];\nreset(xs);\n"),
+        "Cannot pass constant xs to a mut parameter of reset!"
+    );
+    assert_eq!(parse_error("const xs = [[1]];\nfor row in xs {\n    row[0] = 2;\n}\n"), "Cannot assign to loop variable row!");
+}
+
+
+#[test]
+fn test_casts_and_conversions() {
+    parse_ok(concat!(
+        "let a: int = 3.7 as int;\n",
+        "let b: float = 7 as float / 2;\n",
+        "let c: string = 12 as string;\n",
+        "let d: int = true as int;\n",
+        "let e: int? = int(\"42\");\n",
+        "let f: float = float(\"1.5\") ?? 0.0;\n"
+    ));
+
+    assert_eq!(parse_error("let a = \"1\" as int;"), "Cannot cast string to int, use int(...) to parse it!");
+    assert_eq!(parse_error("let a = [1] as string;"), "Cannot cast [int] to string!");
+    assert_eq!(parse_error("let a = int(5);"), "int() expects a string, got int!");
+    assert_eq!(parse_error("let a: int = int(\"5\");"), "Different data types in expression and actual data type.");
+}
+
+
+#[test]
+fn test_try_catch_and_throw() {
+    parse_ok(concat!(
+        "function check(a: int): int {\n",
+        "    if a == 0 {\n",
+        "        throw \"a is zero\";\n",
+        "    }\n",
+        "    return a;\n",
+        "}\n",
+        "try {\n",
+        "    let a = check(0);\n",
+        "} catch error {\n",
+        "    let message: string = error;\n",
+        "}\n",
+        "let a = 1;\n"
+    ));
+
+    assert_eq!(parse_error("throw 5;"), "Throw expects a string message, got int!");
+    assert_eq!(parse_error("try {\n} catch error {\n}\nlet b = error;\n"), "Variable error not found!");
+    assert_eq!(parse_error("try {\n    let a = 1;\n} catch error {\n}\nlet b = a;\n"), "Variable a not found!");
+}
+
+
+#[test]
+fn test_call_expression_callee() {
+    parse_ok(concat!(
+        "function make_adder(n: int): function(int): int {\n",
+        "    return function(x: int): int {\n",
+        "        return x + n;\n",
+        "    };\n",
+        "}\n",
+        "let a: int = make_adder(1)(2);\n",
+        "let handlers = [make_adder(1), make_adder(2)];\n",
+        "let b: int = handlers[0](3);\n",
+        "handlers[1](4);\n",
+        "let c: int = (function(x: int): int { return x; })(3);\n"
+    ));
+
+    assert_eq!(parse_error("let a = 1;\nlet b = a(2);\n"), "Cannot call a of type int!");
+    assert_eq!(
+        parse_error("let f = function(x: int): int { return x; };\nlet a = [f][0](1, 2);\n"),
+        "Function expression expects 1 arguments, got 2!"
+    );
+}
+
+
+#[test]
+fn test_nested_function_literal_keeps_return_context() {
+    parse_ok(concat!(
+        "function outer(): function(): int {\n",
+        "    let a = 1;\n",
+        "    let inner = function(): int {\n",
+        "        return a;\n",
+        "    };\n",
+        "    return inner;\n",
+        "}\n"
+    ));
+}
+
+
+#[test]
+fn test_impl_methods() {
+    parse_ok(concat!(
+        "impl int {\n",
+        "    function add(self, other: int): int {\n",
+        "        return self + other;\n",
+        "    }\n",
+        "}\n",
+        "let a: int = 1.add(2).add(3);\n",
+        "let t = (1, 2);\n",
+        "let b: int = t.0.add(t.1);\n"
+    ));
+
+    assert_eq!(parse_error("let a = 1.twice();"), "Type int has no method twice!");
+    assert_eq!(parse_error("impl int {\n    function zero(): int {\n        return 0;\n    }\n}\n"), "Method int.zero must take self as its first parameter!");
+    assert_eq!(
+        parse_error("impl int {\n    function add(self, other: int): int {\n        return self + other;\n    }\n}\nlet a = 1.add(\"2\");\n"),
+        "Function signature mismatch"
+    );
+    assert_eq!(
+        parse_error("impl [int] {\n    function clear(mut self) {\n    }\n}\nconst xs = [1];\nxs.clear();\n"),
+        "Cannot pass constant xs to a mut parameter of [int].clear!"
+    );
+}
+
+
+#[test]
+fn test_interfaces() {
+    let interface = concat!(
+        "interface Shape {\n",
+        "    function area(self): int;\n",
+        "}\n"
+    );
+
+    parse_ok(&format!("{}{}", interface, concat!(
+        "impl Shape for int {\n",
+        "    function area(self): int {\n",
+        "        return self * self;\n",
+        "    }\n",
+        "}\n",
+        "let shapes: [Shape] = [1, 2];\n",
+        "let shape: Shape = 3;\n",
+        "let area: int = shape.area() + shapes[0].area();\n"
+    )));
+
+    assert_eq!(parse_error(&format!("{}let shape: Shape = 3;\n", interface)), "Type int does not implement Shape!");
+    assert_eq!(
+        parse_error(&format!("{}impl Shape for int {{\n}}\n", interface)),
+        "Type int is missing method area of Shape!"
+    );
+    assert_eq!(
+        parse_error(&format!("{}impl Shape for int {{\n    function area(self): string {{\n        return \"\";\n    }}\n}}\n", interface)),
+        "Method int.area does not match area of Shape!"
+    );
+    assert_eq!(
+        parse_error(&format!("{}impl Shape for int {{\n    function area(self): int {{\n        return 1;\n    }}\n}}\nlet shape: Shape = 3;\nshape.draw();\n", interface)),
+        "Interface Shape has no method draw!"
+    );
+    assert_eq!(parse_error("impl Drawable for int {\n}\n"), "Unknown interface Drawable!");
+    assert_eq!(
+        parse_error(&format!("{}if true {{\n    impl Shape for int {{\n        function area(self): int {{\n            return 1;\n        }}\n    }}\n}}\nlet shape: Shape = 3;\n", interface)),
+        "Type int does not implement Shape!"
+    );
+
+    let implementation = "impl Shape for int {\n    function area(self): int {\n        return 1;\n    }\n}\n";
+    parse_ok(&format!("{}{}let xs = [[1]];\nlet shapes: [[Shape]] = xs;\nlet pairs: {{string: (Shape, int)?}} = {{\"a\": (2, 3)}};\n", interface, implementation));
+    assert_eq!(
+        parse_error(&format!("{}{}let xs = [1.5];\nlet shapes: [Shape] = xs;\n", interface, implementation)),
+        "Different data types in expression and actual data type."
+    );
+    assert_eq!(
+        parse_error(&format!("{}{}function total(shapes: [(Shape, int)]): int {{\n    return 0;\n}}\nlet xs = [(1.5, 1)];\ntotal(xs);\n", interface, implementation)),
+        "Function signature mismatch"
+    );
+}
+
+
+#[test]
+fn test_operator_overloading() {
+    parse_ok(concat!(
+        "impl (int, int) {\n",
+        "    function add(self, other: (int, int)): (int, int) {\n",
+        "        return (self.0 + other.0, self.1 + other.1);\n",
+        "    }\n",
+        "    function less(self, other: (int, int)): bool {\n",
+        "        return self.0 < other.0;\n",
+        "    }\n",
+        "}\n",
+        "let a: (int, int) = (1, 2) + (3, 4);\n",
+        "let b: bool = a >= (1, 1);\n",
+        "a += (1, 1);\n"
+    ));
+
+    assert_eq!(parse_error("let a = (1, 2) * (3, 4);"), "Arithmetic type error!");
+    assert_eq!(
+        parse_error("impl (int, int) {\n    function less(self, other: int): bool {\n        return true;\n    }\n}\nlet a = (1, 2) > 5;\n"),
+        "Arithmetic type error!"
+    );
+    assert_eq!(parse_error("let a = (1, 2);\na += (3, 4);\n"), "Arithmetic type error!");
+    assert_eq!(
+        parse_error("impl (int, int) {\n    function add(self, other: int): int {\n        return other;\n    }\n}\nlet a = (1, 2) + \"3\";\n"),
+        "Function signature mismatch"
+    );
+}
+
+
+#[test]
+fn test_destructuring_declarations() {
+    parse_ok(concat!(
+        "let (a, (b, _)) = (1, (\"two\", 3.0));\n",
+        "let x: int = a;\n",
+        "let y: string = b;\n",
+        "let [first, second] = [[1], [2, 3]];\n",
+        "let z: [int] = second;\n",
+        "for [left, _] in [[1, 2]] {\n",
+        "    let w: int = left;\n",
+        "}\n"
+    ));
+
+    assert_eq!(parse_error("let (a, b) = (1, 2, 3);\n"), "Cannot destructure (int, int, int) into 2 names!");
+    assert_eq!(parse_error("let [a, b] = (1, 2);\n"), "Cannot destructure (int, int) into 2 names!");
+    assert_eq!(parse_error("let (a, b): (int, string) = (1, 2);\n"), "Different data types in expression and actual data type.");
+    assert_eq!(parse_error("let (a, b) = (1, 2);\nlet c: string = b;\n"), "Different data types in expression and actual data type.");
+    assert_eq!(parse_error("const (a, b) = (1, 2);\na = 3;\n"), "Cannot assign to constant a!");
+    assert_eq!(parse_error("let (a, _) = (1, 2);\nlet b = _;\n"), "Variable _ not found!");
+}
+
+
+#[test]
+fn test_range_types() {
+    parse_ok(concat!(
+        "let step = 2;\n",
+        "let r: range = 0..10 step step;\n",
+        "let inside: bool = 3 in r;\n",
+        "for i in 10..=0 step -1 {\n",
+        "    let j: int = i;\n",
+        "}\n",
+        "let next = function(): string? {\n",
+        "    return null;\n",
+        "};\n",
+        "for item in next {\n",
+        "    let s: string = item;\n",
+        "}\n"
+    ));
+
+    assert_eq!(parse_error("let r = 0..1.5;\n"), "Range bounds must be int, got float!");
+    assert_eq!(parse_error("let r = 0..3 step \"1\";\n"), "Range bounds must be int, got string!");
+    assert_eq!(parse_error("let a = \"1\" in 0..3;\n"), "Cannot check if string is in range!");
+    assert_eq!(
+        parse_error("for i in function(): int {\n    return 1;\n} {\n}\n"),
+        "An iterator function must return a nullable type, got int!"
+    );
+}
+
+
+#[test]
+fn test_generators() {
+    parse_ok(concat!(
+        "function evens(limit: int): function(): int? {\n",
+        "    for i in 0..limit step 2 {\n",
+        "        yield i;\n",
+        "    }\n",
+        "}\n",
+        "function stop(): function(): string? {\n",
+        "    yield \"once\";\n",
+        "    return;\n",
+        "}\n",
+        "for n in evens(10) {\n",
+        "    let m: int = n;\n",
+        "}\n",
+        "let next: function(): int? = evens(4);\n"
+    ));
+
+    assert_eq!(parse_error("yield 1;\n"), "No function context for yield!");
+    assert_eq!(
+        parse_error("function f(): int {\n    yield 1;\n}\n"),
+        "A generator must return an iterator function like function(): int?!"
+    );
+    assert_eq!(
+        parse_error("function f(): function(): int? {\n    yield \"a\";\n}\n"),
+        "Cannot yield string from a generator of int!"
+    );
+    assert_eq!(
+        parse_error("function f(): function(): int? {\n    yield 1;\n    return f();\n}\n"),
+        "Generator f cannot return a value, yield it instead!"
+    );
+    assert_eq!(parse_error("function f(): int {\n    return;\n}\n"), "Function f must return a value of type int!");
+    assert_eq!(
+        parse_error("function f(a: int?): function(): int? {\n    a? {\n        yield 1;\n    };\n    yield 2;\n}\n"),
+        "Cannot yield inside the early return on a!"
+    );
+    assert_eq!(
+        parse_error("function f(a: int?): function(): int? {\n    a? {\n        if true {\n            yield 1;\n        }\n    };\n    yield 2;\n}\n"),
+        "Cannot yield inside the early return on a!"
+    );
+}
+
+
+#[test]
+fn test_slice_types() {
+    parse_ok(concat!(
+        "let xs = [1, 2, 3];\n",
+        "let ys: [int] = xs[1:];\n",
+        "let last: int = xs[-1];\n",
+        "let word: string = \"hello\"[:-1];\n",
+        "let letter: string = word[0];\n"
+    ));
+
+    assert_eq!(parse_error("let a = 5[1:];\n"), "Cannot slice int, only lists and strings!");
+    assert_eq!(parse_error("let xs = [1];\nlet a = xs[:\"1\"];\n"), "Slice bounds must be int, got string!");
+    assert_eq!(parse_error("let xs = [1];\nlet a: int = xs[0:1];\n"), "Different data types in expression and actual data type.");
+}
+
+
+fn native_stub(_args: Vec<Value>) -> io::Result<Value> {
+    Ok(Value::new(None, ValueType::Null))
+}
+
+fn parse_with_natives(source: &str) -> Result<(), String> {
+    let mut semantic_analyzer = SemanticAnalyzer::new();
+
+    let len = NativeFunction::from(native_stub, DataType::Int).with_signatures(vec![
+        vec![DataType::String],
+        vec![DataType::List(Box::new(DataType::Generic("T".to_string())))]
+    ]);
+    semantic_analyzer.register_rust_function("len".to_string(), &len);
+    semantic_analyzer.register_rust_function("untyped".to_string(), &NativeFunction::from(native_stub, DataType::Int));
+    semantic_analyzer.register_rust_function("byte_len".to_string(), &NativeFunction::typed(native_stub, vec![DataType::String], DataType::Int));
+
+    let mut lexer = Lexer::new(source.to_string());
+    lexer.lexer().unwrap();
+
+    let mut parser = Parser::with_semantic_analyzer(lexer.get_tokens().clone(), lexer.get_token_pos().clone(), semantic_analyzer);
+    parser.parse_block_statement().map(|_| ()).map_err(|e| e.to_string())
+}
+
+
+#[test]
+fn test_natives_as_function_values() {
+    let functions = concat!(
+        "function apply(f: function(int): int, x: int): int {\n",
+        "    return f(x);\n",
+        "}\n",
+        "function count(f: function([int]): int): int {\n",
+        "    return f([1, 2]);\n",
+        "}\n",
+        "function measure(f: function(string): int): int {\n",
+        "    return f(\"abc\");\n",
+        "}\n",
+        "function first<T>(xs: [T]): T {\n",
+        "    return xs[0];\n",
+        "}\n"
+    );
+
+    parse_with_natives(&format!(
+        "{}let a = count(len);\nlet b = measure(len);\nlet c = measure(byte_len);\nlet d = count(first);\nlet e = len(\"abc\");\n",
+        functions
+    )).unwrap();
+
+    for call in ["apply(len, 5)", "apply(untyped, 5)", "apply(byte_len, 5)", "measure(untyped)"] {
+        assert_eq!(
+            parse_with_natives(&format!("{}let a = {};\n", functions, call)),
+            Err("Function signature mismatch".to_string())
+        );
+    }
+}
@@ -0,0 +1,83 @@
+use std::io;
+
+use core::interpreter::value::{Value, ValueType};
+use core::native_registry::native_registry::NativeRegistry;
+use core::parser::data_type::DataType;
+
+
+// The UTF-8 encoding of a string, one int from 0 to 255 per byte
+pub fn ks_bytes(args: Vec<Value>) -> io::Result<Value> {
+    let text = match args.as_slice() {
+        [text] => match text.get_type() {
+            ValueType::String(text) => text.clone(),
+            _ => return Err(io::Error::new(io::ErrorKind::InvalidData, "Invalid Type!"))
+        },
+        _ => return Err(io::Error::new(io::ErrorKind::InvalidData, "bytes expects a string!"))
+    };
+
+    let mut references: Vec<u64> = Vec::new();
+
+    let native = NativeRegistry::get();
+    {
+        let native = native.borrow();
+        if let Some(local) = &native.local {
+            let mut local = local.borrow_mut();
+
+            for byte in text.bytes() {
+                let reference = local.create_value_without_name(Value::new(None, ValueType::Integer(byte as i64)));
+
+                references.push(reference);
+            }
+        }
+    }
+
+    Ok(Value::new(None, ValueType::List { references, data_type: DataType::Int }))
+}
+
+pub fn ks_byte_len(args: Vec<Value>) -> io::Result<Value> {
+    match args.as_slice() {
+        [text] => match text.get_type() {
+            ValueType::String(text) => Ok(Value::new(None, ValueType::Integer(text.len() as i64))),
+            _ => Err(io::Error::new(io::ErrorKind::InvalidData, "Invalid Type!"))
+        },
+        _ => Err(io::Error::new(io::ErrorKind::InvalidData, "byte_len expects a string!"))
+    }
+}
+
+// Decodes UTF-8 bytes, invalid text gives null
+pub fn ks_from_bytes(args: Vec<Value>) -> io::Result<Value> {
+    let references = match args.as_slice() {
+        [bytes] => match bytes.get_type() {
+            ValueType::List { references, data_type: _ } => references.clone(),
+            _ => return Err(io::Error::new(io::ErrorKind::InvalidData, "Invalid Type!"))
+        },
+        _ => return Err(io::Error::new(io::ErrorKind::InvalidData, "from_bytes expects a list of bytes!"))
+    };
+
+    let mut bytes: Vec<u8> = Vec::new();
+
+    let native = NativeRegistry::get();
+    {
+        let native = native.borrow();
+        if let Some(local) = &native.local {
+            let local = local.borrow();
+
+            for reference in references {
+                let byte = match local.get_by_reference(reference)?.get_type() {
+                    ValueType::Integer(byte) => *byte,
+                    _ => return Err(io::Error::new(io::ErrorKind::InvalidData, "Invalid Type!"))
+                };
+
+                let byte = u8::try_from(byte)
+                    .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, format!("Byte {} is not in 0..=255!", byte)))?;
+
+                bytes.push(byte);
+            }
+        }
+    }
+
+    match String::from_utf8(bytes) {
+        Ok(text) => Ok(Value::new(None, ValueType::String(text))),
+        Err(_) => Ok(Value::new(None, ValueType::Null))
+    }
+}
@@ -0,0 +1,40 @@
+use std::io;
+
+use core::interpreter::value::{Value, ValueType};
+use core::native_registry::native_registry::NativeRegistry;
+use core::parser::data_type::DataType;
+
+
+pub fn ks_enumerate(args: Vec<Value>) -> io::Result<Value> {
+    if args.len() != 1 {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "enumerate expects a list!"));
+    }
+
+    let (items, item_type) = match args[0].get_type() {
+        ValueType::List { references, data_type } => (references, data_type),
+        _ => return Err(io::Error::new(io::ErrorKind::InvalidData, "Invalid Type!"))
+    };
+
+    let pair_type = DataType::Tuple(vec![DataType::Int, item_type.clone()]);
+    let mut references: Vec<u64> = Vec::new();
+
+    let native = NativeRegistry::get();
+    {
+        let native = native.borrow();
+        if let Some(local) = &native.local {
+            let mut local = local.borrow_mut();
+
+            for (i, reference) in items.iter().enumerate() {
+                let item = local.get_by_reference(*reference)?;
+
+                let index = local.create_value_without_name(Value::new(None, ValueType::Integer(i as i64)));
+                let item = local.create_value_without_name(Value::new(None, item.get_type().clone()));
+
+                let pair = Value::new(None, ValueType::Tuple { references: vec![index, item], data_types: pair_type.clone() });
+                references.push(local.create_value_without_name(pair));
+            }
+        }
+    }
+
+    Ok(Value::new(None, ValueType::List { references, data_type: pair_type }))
+}
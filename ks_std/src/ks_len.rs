@@ -9,11 +9,21 @@ pub fn ks_len(args: Vec<Value>) -> io::Result<Value> {
     }
     
     match args[0].get_type() {
+        // Characters, not bytes, so it agrees with indexing and `for c in text`
         ValueType::String(str) => {
-            Ok(Value::new(None, ValueType::Integer(str.len() as i32)))
+            Ok(Value::new(None, ValueType::Integer(str.chars().count() as i64)))
         },
         ValueType::List { references, data_type: _ } => {
-            Ok(Value::new(None, ValueType::Integer(references.len() as i32)))
+            Ok(Value::new(None, ValueType::Integer(references.len() as i64)))
+        },
+        ValueType::Map { references, key_type: _, value_type: _ } => {
+            Ok(Value::new(None, ValueType::Integer(references.len() as i64)))
+        },
+        ValueType::Set { values, data_type: _ } => {
+            Ok(Value::new(None, ValueType::Integer(values.len() as i64)))
+        },
+        ValueType::Range(range) => {
+            Ok(Value::new(None, ValueType::Integer(range.len()?)))
         },
         _ => Err(io::Error::new(io::ErrorKind::InvalidData, "Invalid Type!"))
     }
@@ -1,35 +1,17 @@
 use std::io;
 
+use core::interpreter::range::Range;
 use core::interpreter::value::{Value, ValueType};
-use core::native_registry::native_registry::NativeRegistry;
-use core::parser::data_type::DataType;
 
 
+// `range(n)` is `0..n`, the numbers are produced while the loop runs
 pub fn ks_range(args: Vec<Value>) -> io::Result<Value> {
     if args.len() != 1 {
-        return Err(io::Error::new(io::ErrorKind::InvalidData, "Too many arguments!"));
+        return Err(io::Error::new(io::ErrorKind::InvalidData, format!("range expects 1 argument, got {}!", args.len())));
     }
- 
-    let mut references: Vec<u64> = Vec::new();
 
-    let arg = &args[0];
-    if let ValueType::Integer(number) = arg.get_type() {
-        let native = NativeRegistry::get();
-        {
-            let native = native.borrow();
-            let local = &native.local;
-            if let Some(local) = local {
-                let mut local = local.borrow_mut();
-
-                for i in 0..(number.clone()) {
-                    let value = Value::new(None, ValueType::Integer(i));
-                    let reference = local.create_value_without_name(value);
-
-                    references.push(reference);
-                }
-            } 
-        }
+    match args[0].get_type() {
+        ValueType::Integer(number) => Ok(Value::new(None, ValueType::Range(Range::new(0, *number, 1, false)?))),
+        _ => Err(io::Error::new(io::ErrorKind::InvalidData, "Invalid Type!"))
     }
-
-    Ok(Value::new(None, ValueType::List { references, data_type: DataType::Int }))
-}
\ No newline at end of file
+}
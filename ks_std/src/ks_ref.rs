@@ -10,7 +10,10 @@ pub fn ks_ref(args: Vec<Value>) -> io::Result<Value> {
     let value = args[0].clone();
     let reference = value.get_reference();
     if let Some(reference) = reference {
-        Ok(Value::new(None, ValueType::Integer(reference as i32)))
+        let reference = i64::try_from(reference)
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, format!("Reference {} does not fit into int!", reference)))?;
+
+        Ok(Value::new(None, ValueType::Integer(reference)))
     } else {
         Ok(Value::new(None, ValueType::Null))
     }
@@ -0,0 +1,40 @@
+use std::io;
+
+use core::interpreter::value::{Value, ValueType};
+use core::native_registry::native_registry::NativeRegistry;
+use core::parser::data_type::DataType;
+
+
+pub fn ks_split(args: Vec<Value>) -> io::Result<Value> {
+    if args.len() != 2 {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "split expects a string and a separator!"));
+    }
+
+    let (text, separator) = match (args[0].get_type(), args[1].get_type()) {
+        (ValueType::String(text), ValueType::String(separator)) => (text, separator),
+        _ => return Err(io::Error::new(io::ErrorKind::InvalidData, "Invalid Type!"))
+    };
+
+    if separator.is_empty() {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "Cannot split by an empty separator!"));
+    }
+
+    let mut references: Vec<u64> = Vec::new();
+
+    let native = NativeRegistry::get();
+    {
+        let native = native.borrow();
+        if let Some(local) = &native.local {
+            let mut local = local.borrow_mut();
+
+            for part in text.split(separator.as_str()) {
+                let value = Value::new(None, ValueType::String(part.to_string()));
+                let reference = local.create_value_without_name(value);
+
+                references.push(reference);
+            }
+        }
+    }
+
+    Ok(Value::new(None, ValueType::List { references, data_type: DataType::String }))
+}
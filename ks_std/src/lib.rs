@@ -8,22 +8,51 @@ mod ks_len;
 mod ks_range;
 mod ks_ref;
 mod ks_local;
+mod ks_split;
+mod ks_enumerate;
+mod ks_bytes;
 
 use ks_print::{ks_print, ks_println};
 use ks_len::ks_len;
 use ks_range::ks_range;
 use ks_ref::ks_ref;
 use ks_local::ks_local;
+use ks_split::ks_split;
+use ks_enumerate::ks_enumerate;
+use ks_bytes::{ks_byte_len, ks_bytes, ks_from_bytes};
 
 pub fn ks_register_std() {
     let mut buffer = NativeBuffer::new();
 
-    buffer.add_function("print", NativeFunction::process(ks_print));
-    buffer.add_function("println", NativeFunction::process(ks_println));
-    buffer.add_function("len", NativeFunction::from(ks_len, DataType::Int));
-    buffer.add_function("range", NativeFunction::from(ks_range, DataType::List(Box::new(DataType::Int))));
-    buffer.add_function("ref", NativeFunction::from(ks_ref, DataType::Int));
+    buffer.add_function("print", NativeFunction::printer(ks_print));
+    buffer.add_function("println", NativeFunction::printer(ks_println));
+    buffer.add_function("len", NativeFunction::from(ks_len, DataType::Int).with_signatures(vec![
+        vec![DataType::String],
+        vec![DataType::List(Box::new(DataType::Generic("T".to_string())))],
+        vec![DataType::Map(Box::new(DataType::Generic("K".to_string())), Box::new(DataType::Generic("V".to_string())))],
+        vec![DataType::Set(Box::new(DataType::Generic("T".to_string())))],
+        vec![DataType::Range]
+    ]));
+    buffer.add_function("range", NativeFunction::typed(ks_range, vec![DataType::Int], DataType::Range));
+    buffer.add_function("ref", NativeFunction::from(ks_ref, DataType::nullable(DataType::Int)));
     buffer.add_function("show_local", NativeFunction::process(ks_local));
+    buffer.add_function("split", NativeFunction::typed(
+        ks_split,
+        vec![DataType::String, DataType::String],
+        DataType::List(Box::new(DataType::String))
+    ));
+    buffer.add_function("enumerate", NativeFunction::typed(
+        ks_enumerate,
+        vec![DataType::List(Box::new(DataType::Generic("T".to_string())))],
+        DataType::List(Box::new(DataType::Tuple(vec![DataType::Int, DataType::Generic("T".to_string())])))
+    ));
+    buffer.add_function("bytes", NativeFunction::typed(ks_bytes, vec![DataType::String], DataType::List(Box::new(DataType::Int))));
+    buffer.add_function("byte_len", NativeFunction::typed(ks_byte_len, vec![DataType::String], DataType::Int));
+    buffer.add_function("from_bytes", NativeFunction::typed(
+        ks_from_bytes,
+        vec![DataType::List(Box::new(DataType::Int))],
+        DataType::nullable(DataType::String)
+    ));
 
     let registry = NativeRegistry::get();
     {